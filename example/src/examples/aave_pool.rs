@@ -8,13 +8,17 @@
 
 use alloy::primitives::Address;
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::rpc::client::RpcClient;
 use alloy::signers::Signer;
 use alloy::sol;
-use alloy_transport_window::{WindowSigner, WindowTransport};
+use alloy_transport_window::{
+    WindowProvider, WindowProviderBuilderExt, WindowSigner, WindowTransport,
+};
 use dioxus::logger::tracing;
 use dioxus::prelude::*;
 
+/// Arbitrum One chain ID, where the default pool is deployed
+const ARBITRUM_CHAIN_ID: u64 = 42161;
+
 // Define the Aave Pool interface using sol! macro
 sol! {
     #[sol(rpc)]
@@ -90,8 +94,18 @@ pub fn AavePool() -> Element {
                 }
             };
 
-            let client = RpcClient::new(transport, false);
-            let provider = ProviderBuilder::new().connect_client(client);
+            // The default pool lives on Arbitrum - ask the wallet to switch if needed
+            status_msg.set("Checking network...".to_string());
+            if let Err(e) = WindowProvider::from_transport(transport.clone())
+                .ensure_chain(ARBITRUM_CHAIN_ID)
+                .await
+            {
+                error_msg.set(Some(format!("Wrong network: {}", e)));
+                status_msg.set("Error".to_string());
+                return;
+            }
+
+            let provider = ProviderBuilder::new().connect_window_transport(transport);
 
             // Verify the contract exists at this address
             let code_len = match provider.get_code_at(pool_addr).await {
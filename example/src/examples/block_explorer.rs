@@ -7,8 +7,7 @@
 //! - Query blockchain state
 
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::rpc::client::RpcClient;
-use alloy_transport_window::WindowTransport;
+use alloy_transport_window::{use_window_provider, WindowProviderBuilderExt};
 use dioxus::prelude::*;
 
 #[component]
@@ -19,27 +18,25 @@ pub fn BlockExplorer() -> Element {
     let mut error_msg = use_signal(|| Option::<String>::None);
     let mut status_msg = use_signal(|| String::from("Ready"));
     let mut is_loading = use_signal(|| false);
+    let window_provider = use_window_provider();
 
     // Fetch latest block
     let fetch_block = move |_| {
+        let window_provider = window_provider.clone();
         spawn(async move {
             is_loading.set(true);
             error_msg.set(None);
             status_msg.set("Fetching latest block...".to_string());
 
-            // Create provider (no wallet needed for read operations)
-            let transport = match WindowTransport::new() {
-                Ok(t) => t,
-                Err(e) => {
-                    error_msg.set(Some(format!("Transport error: {}", e)));
-                    status_msg.set("Error".to_string());
-                    is_loading.set(false);
-                    return;
-                }
+            // Reuse the app-wide transport (no wallet needed for read operations)
+            let Some(window_provider) = window_provider else {
+                error_msg.set(Some("No wallet found".to_string()));
+                status_msg.set("Error".to_string());
+                is_loading.set(false);
+                return;
             };
-
-            let client = RpcClient::new(transport, false);
-            let provider = ProviderBuilder::new().connect_client(client);
+            let provider = ProviderBuilder::new()
+                .connect_window_transport(window_provider.transport().clone());
 
             // Fetch latest block number
             match provider.get_block_number().await {
@@ -13,10 +13,11 @@
 use alloy::network::TransactionBuilder;
 use alloy::primitives::{Address, U256};
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::rpc::client::RpcClient;
 use alloy::rpc::types::TransactionRequest;
 use alloy::signers::Signer;
-use alloy_transport_window::{WindowSigner, WindowTransport};
+use alloy_transport_window::{
+    SimulationResult, WindowProvider, WindowProviderBuilderExt, WindowSigner, WindowTransport,
+};
 use dioxus::logger::tracing;
 use dioxus::prelude::*;
 
@@ -112,15 +113,32 @@ pub fn SendTransaction() -> Element {
                 }
             };
 
-            let client = RpcClient::new(transport, false);
-            let provider = ProviderBuilder::new().connect_client(client);
+            let window_provider = WindowProvider::from_transport(transport.clone());
+            let provider = ProviderBuilder::new().connect_window_transport(transport);
 
-            // Build transaction with from field (important!)
+            // Build transaction with from field (important! WindowTransport::with_default_from
+            // can fill it in for the send, but simulation needs it too)
             let tx = TransactionRequest::default()
                 .with_from(wallet_addr)
                 .with_to(to_addr)
                 .with_value(amount_wei);
 
+            // Simulate first so a failing transaction doesn't cost a wallet prompt
+            status_msg.set("Simulating transaction...".to_string());
+            match window_provider.simulate(&tx).await {
+                Ok(SimulationResult::Success(_)) => {}
+                Ok(SimulationResult::Reverted { reason, message }) => {
+                    error_msg.set(Some(format!(
+                        "Transaction would fail: {}",
+                        reason.unwrap_or(message)
+                    )));
+                    status_msg.set("Simulation failed".to_string());
+                    is_sending.set(false);
+                    return;
+                }
+                Err(e) => tracing::warn!("Simulation unavailable: {}", e),
+            }
+
             status_msg.set("Sending transaction (wallet will prompt)...".to_string());
 
             // Send transaction - WindowTransport routes to eth_sendTransaction
@@ -6,10 +6,8 @@
 //! - Account balance
 
 use alloy::primitives::{Address, U256};
-use alloy::providers::{Provider, ProviderBuilder};
-use alloy::rpc::client::RpcClient;
 use alloy::signers::Signer;
-use alloy_transport_window::{WindowSigner, WindowTransport};
+use alloy_transport_window::{format_ether, WindowProvider, WindowSigner};
 use alloy_dyn_abi::eip712::TypedData;
 use dioxus::prelude::*;
 use serde::Serialize;
@@ -73,27 +71,18 @@ pub fn WalletConnect() -> Element {
                 Ok(signer) => {
                     let addr = signer.address();
                     wallet_address.set(Some(addr));
+                    let provider = WindowProvider::from_transport(signer.transport());
                     window_signer.set(Some(signer));
                     status_msg.set("Connected!".to_string());
 
-                    // Create provider and fetch basic data
-                    match WindowTransport::new() {
-                        Ok(transport) => {
-                            let client = RpcClient::new(transport, false);
-                            let provider = ProviderBuilder::new().connect_client(client);
-
-                            // Fetch chain ID
-                            if let Ok(id) = provider.get_chain_id().await {
-                                chain_id.set(Some(id));
-                            }
-
-                            // Fetch balance
-                            if let Ok(bal) = provider.get_balance(addr).await {
-                                balance.set(Some(bal));
-                            }
+                    // Fetch chain ID and balance in a single round-trip
+                    match provider.account_overview().await {
+                        Ok(overview) => {
+                            chain_id.set(Some(overview.chain_id));
+                            balance.set(Some(overview.balance));
                         }
                         Err(e) => {
-                            error_msg.set(Some(format!("Transport error: {}", e)));
+                            error_msg.set(Some(format!("Failed to load account: {}", e)));
                         }
                     }
                 }
@@ -172,13 +161,13 @@ pub fn WalletConnect() -> Element {
                     }
 
                     // Balance
-                    if let Some(bal) = balance() {
+                    if let Some(bal) = balance().map(|wei| format_ether(wei, 4)) {
                         div { class: "p-4 bg-gray-900/50 rounded-lg border border-gray-700/50",
                             p { class: "text-xs font-semibold text-gray-400 mb-1",
                                 "Balance"
                             }
                             p { class: "text-lg font-bold text-green-400 font-mono break-all",
-                                "{bal} wei"
+                                "{bal} ETH"
                             }
                         }
                     }
@@ -1,3 +1,4 @@
+use alloy_transport_window::use_provide_window_provider;
 use dioxus::prelude::*;
 
 mod examples;
@@ -21,6 +22,9 @@ fn main() {
 
 #[component]
 fn App() -> Element {
+    // Share one provider, and its wallet listeners, with every example
+    use_provide_window_provider();
+
     rsx! {
         document::Link { rel: "icon", href: FAVICON }
         document::Link { rel: "stylesheet", href: MAIN_CSS }
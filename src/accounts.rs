@@ -0,0 +1,144 @@
+//! Account list parsing tolerant of non-MetaMask response shapes
+
+use alloy_primitives::Address;
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+
+use crate::error::{Result, WindowError};
+#[cfg(target_arch = "wasm32")]
+use crate::js_bindings::{ethereum_request, legacy_enable};
+
+/// Parse an `eth_requestAccounts` / `eth_accounts` / `accountsChanged` payload
+///
+/// Besides the plain string array MetaMask returns, this accepts an object
+/// wrapping the array in a `result` field, a single unwrapped account string
+/// (returned by some mobile wallets) and CAIP-10 account IDs
+/// (`eip155:1:0x...`).
+pub(crate) fn parse_accounts(value: &Value) -> Result<Vec<Address>> {
+    match value {
+        Value::Array(accounts) => accounts.iter().map(parse_account).collect(),
+        Value::Object(obj) if obj.contains_key("result") => parse_accounts(&obj["result"]),
+        Value::String(account) if account.trim().is_empty() => Ok(Vec::new()),
+        Value::String(_) => Ok(vec![parse_account(value)?]),
+        Value::Null => Ok(Vec::new()),
+        _ => Err(WindowError::InvalidAddress(value.to_string())),
+    }
+}
+
+/// Parse an account list straight from a wallet response or event payload
+pub(crate) fn accounts_from_js(value: JsValue) -> Result<Vec<Address>> {
+    if value.is_null() || value.is_undefined() {
+        return Ok(Vec::new());
+    }
+    parse_accounts(&serde_wasm_bindgen::from_value(value)?)
+}
+
+/// Request accounts via `eth_requestAccounts`
+///
+/// Legacy providers without `eth_requestAccounts` fall back to the deprecated
+/// `ethereum.enable()`; the original error is kept if that's missing too.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn request_accounts_with_enable_fallback(
+    ethereum: &JsValue,
+) -> Result<Vec<Address>> {
+    let params = js_sys::Array::new();
+    let promise = ethereum_request(ethereum, "eth_requestAccounts", &params);
+    let accounts = match wasm_bindgen_futures::JsFuture::from(promise)
+        .await
+        .map_err(WindowError::from)
+    {
+        Err(e @ WindowError::MethodNotFound(_)) | Err(e @ WindowError::UnsupportedMethod(_)) => {
+            match legacy_enable(ethereum).await {
+                Some(result) => result?,
+                None => return Err(e),
+            }
+        }
+        result => result?,
+    };
+    accounts_from_js(accounts)
+}
+
+/// Parse a single account, taking the trailing component of CAIP-10 IDs
+fn parse_account(account: &Value) -> Result<Address> {
+    let raw = account
+        .as_str()
+        .ok_or_else(|| WindowError::InvalidAddress(account.to_string()))?;
+    let address = raw.rsplit(':').next().unwrap_or(raw);
+
+    address
+        .trim()
+        .parse()
+        .map_err(|e| WindowError::InvalidAddress(format!("{} ({})", raw, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ADDRESS: &str = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+
+    fn address() -> Address {
+        ADDRESS.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_account_arrays() {
+        assert_eq!(parse_accounts(&json!([ADDRESS])).unwrap(), vec![address()]);
+        assert_eq!(
+            parse_accounts(&json!([ADDRESS.to_lowercase()])).unwrap(),
+            vec![address()]
+        );
+        assert!(parse_accounts(&json!([])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parses_wrapped_and_caip10_accounts() {
+        assert_eq!(
+            parse_accounts(&json!({ "result": [ADDRESS] })).unwrap(),
+            vec![address()]
+        );
+        assert_eq!(
+            parse_accounts(&json!([format!("eip155:1:{}", ADDRESS)])).unwrap(),
+            vec![address()]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_accounts() {
+        for invalid in [json!(["0x1234"]), json!([1]), json!(true), json!({})] {
+            assert!(matches!(
+                parse_accounts(&invalid),
+                Err(WindowError::InvalidAddress(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn parses_single_account_strings() {
+        assert_eq!(parse_accounts(&json!(ADDRESS)).unwrap(), vec![address()]);
+        assert_eq!(
+            parse_accounts(&json!(format!("eip155:1:{}", ADDRESS))).unwrap(),
+            vec![address()]
+        );
+        assert!(parse_accounts(&json!("")).unwrap().is_empty());
+        assert!(parse_accounts(&json!("  ")).unwrap().is_empty());
+        assert!(parse_accounts(&Value::Null).unwrap().is_empty());
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn parses_accounts_from_js() {
+        let accounts = js_sys::Array::of1(&JsValue::from_str(
+            "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045",
+        ));
+        assert_eq!(accounts_from_js(accounts.into()).unwrap().len(), 1);
+        assert!(accounts_from_js(JsValue::UNDEFINED).unwrap().is_empty());
+        assert!(accounts_from_js(JsValue::NULL).unwrap().is_empty());
+    }
+}
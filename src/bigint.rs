@@ -0,0 +1,112 @@
+//! Method-aware encoding of param fields as JS BigInts
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use alloy_primitives::U256;
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+
+use crate::units::from_hex_quantity;
+
+/// Param paths to encode as BigInt, per method
+///
+/// A path is a list of segments, each an object key, an array index or `*`
+/// for any key or index. Cheap to clone.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BigIntParams {
+    methods: Rc<HashMap<String, Vec<Vec<String>>>>,
+}
+
+impl BigIntParams {
+    /// Add dot-separated `paths` for `method`
+    pub(crate) fn with(mut self, method: String, paths: &[&str]) -> Self {
+        Rc::make_mut(&mut self.methods)
+            .entry(method)
+            .or_default()
+            .extend(
+                paths
+                    .iter()
+                    .map(|path| path.split('.').map(str::to_string).collect()),
+            );
+        self
+    }
+
+    /// Paths configured for `method`, empty if none
+    pub(crate) fn paths(&self, method: &str) -> &[Vec<String>] {
+        self.methods.get(method).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Whether `path` matches any of `paths`
+pub(crate) fn matches(paths: &[Vec<String>], path: &[String]) -> bool {
+    paths.iter().any(|pattern| {
+        pattern.len() == path.len()
+            && pattern
+                .iter()
+                .zip(path)
+                .all(|(pattern, segment)| pattern == "*" || pattern == segment)
+    })
+}
+
+/// Encode a hex quantity or integer as a JS BigInt, `None` for other values
+pub(crate) fn to_bigint(value: &Value) -> Option<JsValue> {
+    let value = match value {
+        Value::String(s) => from_hex_quantity(s).ok()?,
+        Value::Number(n) => U256::from(n.as_u64()?),
+        _ => return None,
+    };
+    js_sys::BigInt::new(&JsValue::from_str(&value.to_string()))
+        .ok()
+        .map(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(segments: &[&str]) -> Vec<String> {
+        segments.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn matches_paths_with_wildcards() {
+        let params = BigIntParams::default().with("eth_call".to_string(), &["0.value", "*.gas"]);
+        let paths = params.paths("eth_call");
+        assert!(matches(paths, &path(&["0", "value"])));
+        assert!(matches(paths, &path(&["1", "gas"])));
+        assert!(!matches(paths, &path(&["0", "data"])));
+        assert!(!matches(paths, &path(&["0"])));
+        assert!(!matches(paths, &path(&["0", "value", "x"])));
+        assert!(params.paths("eth_sendTransaction").is_empty());
+    }
+
+    #[test]
+    fn accumulates_paths_per_method() {
+        let params = BigIntParams::default()
+            .with("eth_call".to_string(), &["0.value"])
+            .with("eth_call".to_string(), &["0.gas"]);
+        assert_eq!(params.paths("eth_call").len(), 2);
+        // Clones share paths but extend independently
+        let extended = params.clone().with("eth_call".to_string(), &["0.nonce"]);
+        assert_eq!(params.paths("eth_call").len(), 2);
+        assert_eq!(extended.paths("eth_call").len(), 3);
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use serde_json::json;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn encodes_bigints() {
+        let bigint = to_bigint(&json!("0xff")).unwrap();
+        assert!(bigint.is_bigint());
+        assert_eq!(bigint, JsValue::from(js_sys::BigInt::from(255u64)));
+        assert!(to_bigint(&json!(255)).unwrap().is_bigint());
+        assert!(to_bigint(&json!("latest")).is_none());
+        assert!(to_bigint(&json!(1.5)).is_none());
+    }
+}
@@ -0,0 +1,43 @@
+//! Alloy `ProviderBuilder` integration
+
+use alloy_network::Network;
+use alloy_provider::fillers::TxFiller;
+use alloy_provider::{ProviderBuilder, ProviderLayer, RootProvider};
+use alloy_rpc_client::RpcClient;
+
+use crate::error::Result;
+use crate::transport::WindowTransport;
+
+/// Connect an Alloy [`ProviderBuilder`] to window.ethereum
+///
+/// Replaces `ProviderBuilder::new().connect_client(RpcClient::new(transport, false))`
+/// with `ProviderBuilder::new().connect_window()?`. Layers and fillers
+/// configured on the builder are applied as usual.
+pub trait WindowProviderBuilderExt {
+    /// Provider produced by the builder
+    type Provider;
+
+    /// Finish the builder with a new [`WindowTransport`]
+    fn connect_window(self) -> Result<Self::Provider>;
+
+    /// Finish the builder with an already configured [`WindowTransport`]
+    fn connect_window_transport(self, transport: WindowTransport) -> Self::Provider;
+}
+
+impl<L, F, N> WindowProviderBuilderExt for ProviderBuilder<L, F, N>
+where
+    L: ProviderLayer<RootProvider<N>, N>,
+    F: TxFiller<N> + ProviderLayer<L::Provider, N>,
+    N: Network,
+{
+    type Provider = F::Provider;
+
+    fn connect_window(self) -> Result<Self::Provider> {
+        Ok(self.connect_window_transport(WindowTransport::new()?))
+    }
+
+    fn connect_window_transport(self, transport: WindowTransport) -> Self::Provider {
+        // Browser wallets talk to remote nodes, so the client is never local
+        self.connect_client(RpcClient::new(transport, false))
+    }
+}
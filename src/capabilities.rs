@@ -0,0 +1,162 @@
+//! EIP-5792 wallet capabilities
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::events::Subscription;
+use crate::transport::WindowTransport;
+use alloy_primitives::Address;
+use serde_json::{Map, Value};
+
+/// Capabilities reported by `wallet_getCapabilities` (EIP-5792), keyed by chain id
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    chains: HashMap<u64, Map<String, Value>>,
+}
+
+impl Capabilities {
+    /// Parse a `wallet_getCapabilities` response
+    ///
+    /// The response is an object keyed by hex chain id (e.g. `"0x1"`); entries
+    /// with unparseable keys or non-object values are skipped.
+    pub(crate) fn from_response(value: Value) -> Self {
+        let chains = match value {
+            Value::Object(obj) => obj
+                .into_iter()
+                .filter_map(|(chain, caps)| {
+                    let chain_id = u64::from_str_radix(chain.trim_start_matches("0x"), 16).ok()?;
+                    match caps {
+                        Value::Object(caps) => Some((chain_id, caps)),
+                        _ => None,
+                    }
+                })
+                .collect(),
+            _ => HashMap::new(),
+        };
+
+        Self { chains }
+    }
+
+    /// Get the raw capabilities declared for a chain
+    pub fn chain(&self, chain_id: u64) -> Option<&Map<String, Value>> {
+        self.chains.get(&chain_id)
+    }
+
+    /// Iterate over the chain ids the wallet declared capabilities for
+    pub fn chain_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.chains.keys().copied()
+    }
+
+    /// Whether the wallet declared no capabilities (or doesn't support EIP-5792)
+    pub fn is_empty(&self) -> bool {
+        self.chains.is_empty()
+    }
+
+    /// Typed capability descriptors for every chain
+    pub fn typed(&self) -> HashMap<u64, ChainCapabilities> {
+        self.chains
+            .iter()
+            .map(|(chain_id, caps)| (*chain_id, ChainCapabilities::from_map(caps)))
+            .collect()
+    }
+}
+
+/// Typed view of the capabilities a wallet declares for one chain
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChainCapabilities {
+    /// Calls sent with `wallet_sendCalls` execute atomically
+    pub atomic_batch: bool,
+    /// Gas can be sponsored through an ERC-7677 paymaster service
+    pub paymaster_service: bool,
+    /// The wallet can source funds from outside the account
+    pub auxiliary_funds: bool,
+    /// All declared capabilities, including ones without a typed field
+    pub raw: Map<String, Value>,
+}
+
+impl ChainCapabilities {
+    /// Read the known capabilities from a chain's capability object
+    ///
+    /// Atomic batching is reported as `atomicBatch: { supported }` by older
+    /// wallets and as `atomic: { status }` since the final EIP-5792 revision.
+    fn from_map(caps: &Map<String, Value>) -> Self {
+        let supported =
+            |name: &str| caps.get(name).and_then(|c| c["supported"].as_bool()) == Some(true);
+        let atomic_status = caps.get("atomic").and_then(|c| c["status"].as_str());
+
+        Self {
+            atomic_batch: supported("atomicBatch")
+                || matches!(atomic_status, Some("supported") | Some("ready")),
+            paymaster_service: supported("paymasterService"),
+            auxiliary_funds: supported("auxiliaryFunds"),
+            raw: caps.clone(),
+        }
+    }
+}
+
+/// Wallet support learned from earlier requests, shared by all clones of a signer
+///
+/// Lets helpers pick a method without probing (and failing) on every call.
+/// Cleared on `chainChanged`, since capabilities and even method support can
+/// differ between chains.
+#[derive(Clone, Debug)]
+pub(crate) struct CapabilityCache {
+    inner: Rc<RefCell<CacheInner>>,
+    _listener: Rc<Subscription>,
+}
+
+#[derive(Debug, Default)]
+struct CacheInner {
+    /// `wallet_getCapabilities` result and the account it was fetched for
+    capabilities: Option<(Address, HashMap<u64, ChainCapabilities>)>,
+    /// Whether the wallet implements `eth_signTypedData_v4`, `None` until known
+    #[cfg(feature = "eip712")]
+    typed_data_v4: Option<bool>,
+}
+
+impl CapabilityCache {
+    /// Create an empty cache cleared by the transport's `chainChanged` events
+    pub(crate) fn new(transport: &WindowTransport) -> Self {
+        let inner = Rc::new(RefCell::new(CacheInner::default()));
+
+        let listener_inner = inner.clone();
+        let listener = transport.on_chain_changed(move |_| {
+            *listener_inner.borrow_mut() = CacheInner::default();
+        });
+
+        Self {
+            inner,
+            _listener: Rc::new(listener),
+        }
+    }
+
+    /// Cached capabilities of `address`, if fetched since the last chain change
+    pub(crate) fn capabilities(&self, address: Address) -> Option<HashMap<u64, ChainCapabilities>> {
+        match &self.inner.borrow().capabilities {
+            Some((cached, capabilities)) if *cached == address => Some(capabilities.clone()),
+            _ => None,
+        }
+    }
+
+    /// Remember the capabilities of `address`
+    pub(crate) fn set_capabilities(
+        &self,
+        address: Address,
+        capabilities: HashMap<u64, ChainCapabilities>,
+    ) {
+        self.inner.borrow_mut().capabilities = Some((address, capabilities));
+    }
+
+    /// Whether `eth_signTypedData_v4` is supported, `None` until known
+    #[cfg(feature = "eip712")]
+    pub(crate) fn typed_data_v4(&self) -> Option<bool> {
+        self.inner.borrow().typed_data_v4
+    }
+
+    /// Remember whether `eth_signTypedData_v4` is supported
+    #[cfg(feature = "eip712")]
+    pub(crate) fn set_typed_data_v4(&self, supported: bool) {
+        self.inner.borrow_mut().typed_data_v4 = Some(supported);
+    }
+}
@@ -0,0 +1,142 @@
+//! Framework-agnostic wallet connection lifecycle
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use alloy_primitives::Address;
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::stream::{self, Stream, StreamExt};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+
+use crate::accounts::{accounts_from_js, request_accounts_with_enable_fallback};
+use crate::error::{Result, WindowError};
+use crate::events::Subscription;
+use crate::js_bindings::{ethereum_request, get_ethereum};
+use crate::normalize::chain_id_from_js;
+
+/// Connection status of the wallet, for driving UI state
+#[derive(Clone, Debug)]
+pub enum ConnectionState {
+    /// No account is exposed to the site
+    Disconnected,
+    /// Waiting for the user to approve the connection
+    Connecting,
+    /// Connected with an active account
+    Connected {
+        /// Active account
+        address: Address,
+        /// Chain ID the wallet is connected to
+        chain_id: u64,
+    },
+    /// Connecting failed; shared so the state stays cheap to clone
+    Error(Rc<WindowError>),
+}
+
+/// Last known account and chain, used to build transitions from events
+struct Tracker {
+    address: Option<Address>,
+    chain_id: Option<u64>,
+    tx: UnboundedSender<ConnectionState>,
+}
+
+impl Tracker {
+    /// Emit the state implied by the current account and chain
+    fn emit(&self) {
+        let state = match (self.address, self.chain_id) {
+            (Some(address), Some(chain_id)) => ConnectionState::Connected { address, chain_id },
+            (None, _) => ConnectionState::Disconnected,
+            // Account known but chain not yet reported
+            (Some(_), None) => return,
+        };
+        let _ = self.tx.unbounded_send(state);
+    }
+}
+
+impl ConnectionState {
+    /// Connect to the wallet and follow the connection as it changes
+    ///
+    /// The stream yields [`Connecting`](Self::Connecting), then
+    /// [`Connected`](Self::Connected) or [`Error`](Self::Error) once the user
+    /// answers the account prompt, followed by a new state on every account
+    /// switch, chain switch, lock or disconnect. UI frameworks can map it
+    /// into their own reactive primitives (a Dioxus signal, a Leptos
+    /// signal, ...). The wallet listeners are removed when the stream is
+    /// dropped.
+    pub fn watch() -> impl Stream<Item = ConnectionState> {
+        let ethereum = get_ethereum();
+        if ethereum.is_null() || ethereum.is_undefined() {
+            let error = ConnectionState::Error(Rc::new(WindowError::NoWallet));
+            return stream::iter([error]).left_stream();
+        }
+
+        let (tx, rx) = mpsc::unbounded();
+        let tracker = Rc::new(RefCell::new(Tracker {
+            address: None,
+            chain_id: None,
+            tx: tx.clone(),
+        }));
+
+        // Subscribe before prompting so no change during the prompt is missed
+        let accounts_tracker = tracker.clone();
+        let chain_tracker = tracker.clone();
+        let disconnect_tracker = tracker.clone();
+        let subscriptions = [
+            Subscription::new(&ethereum, "accountsChanged", move |accounts| {
+                if let Ok(accounts) = accounts_from_js(accounts) {
+                    let mut tracker = accounts_tracker.borrow_mut();
+                    tracker.address = accounts.first().copied();
+                    tracker.emit();
+                }
+            }),
+            Subscription::new(&ethereum, "chainChanged", move |chain_id| {
+                let mut tracker = chain_tracker.borrow_mut();
+                tracker.chain_id = chain_id_from_js(chain_id).ok();
+                tracker.emit();
+            }),
+            Subscription::new(&ethereum, "disconnect", move |_| {
+                let mut tracker = disconnect_tracker.borrow_mut();
+                tracker.address = None;
+                tracker.emit();
+            }),
+        ];
+
+        let _ = tx.unbounded_send(ConnectionState::Connecting);
+        spawn_local(async move {
+            match connect(&ethereum).await {
+                Ok((address, chain_id)) => {
+                    let mut tracker = tracker.borrow_mut();
+                    tracker.address = Some(address);
+                    tracker.chain_id = Some(chain_id);
+                    tracker.emit();
+                }
+                Err(e) => {
+                    let _ = tx.unbounded_send(ConnectionState::Error(Rc::new(e)));
+                }
+            }
+        });
+
+        // The stream owns the listeners, removing them when dropped
+        stream::unfold((rx, subscriptions), |(mut rx, subscriptions)| async move {
+            let state = rx.next().await?;
+            Some((state, (rx, subscriptions)))
+        })
+        .right_stream()
+    }
+}
+
+/// Request accounts (falling back to legacy `enable()`) and read the chain ID
+async fn connect(ethereum: &JsValue) -> Result<(Address, u64)> {
+    let params = js_sys::Array::new();
+
+    let address = *request_accounts_with_enable_fallback(ethereum)
+        .await?
+        .first()
+        .ok_or(WindowError::NoAccounts)?;
+
+    let chain_id = chain_id_from_js(
+        JsFuture::from(ethereum_request(ethereum, "eth_chainId", &params)).await?,
+    )?;
+
+    Ok((address, chain_id))
+}
@@ -0,0 +1,29 @@
+//! Dioxus integration - share one provider through component context
+
+use dioxus_core::{provide_context, try_consume_context, use_hook};
+
+use crate::provider::WindowProvider;
+
+/// Create a [`WindowProvider`] and provide it to all descendant components
+///
+/// Call once in the root component. Descendants get the same provider, and
+/// so the same transport and wallet event listeners, with
+/// [`use_window_provider`]. Returns `None` (and provides nothing) when no
+/// wallet is installed.
+pub fn use_provide_window_provider() -> Option<WindowProvider> {
+    use_hook(|| match WindowProvider::new() {
+        Ok(provider) => Some(provide_context(provider)),
+        Err(e) => {
+            tracing::debug!("Not providing a WindowProvider: {}", e);
+            None
+        }
+    })
+}
+
+/// Get the [`WindowProvider`] provided by an ancestor component
+///
+/// Returns `None` if no ancestor called [`use_provide_window_provider`] or no
+/// wallet is installed. Clones share the provider's transport and state.
+pub fn use_window_provider() -> Option<WindowProvider> {
+    use_hook(try_consume_context::<WindowProvider>)
+}
@@ -0,0 +1,147 @@
+//! Multi-wallet discovery (EIP-6963)
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::js_bindings::{eip6963_listen, eip6963_unlisten, sleep};
+
+/// Wallet metadata announced with EIP-6963
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ProviderInfo {
+    /// UUIDv4 unique to this provider for the page session
+    pub uuid: String,
+    /// Human-readable wallet name, e.g. `Rabby Wallet`
+    pub name: String,
+    /// Wallet icon as a data URI, suitable for an `<img>` `src`
+    pub icon: String,
+    /// Reverse-DNS identifier of the wallet, e.g. `io.rabby`
+    pub rdns: String,
+}
+
+/// A wallet announced with EIP-6963: its metadata and EIP-1193 provider
+#[derive(Clone, Debug)]
+pub struct ProviderDetail {
+    /// Wallet metadata
+    pub info: ProviderInfo,
+    /// EIP-1193 provider object of the wallet
+    pub provider: JsValue,
+}
+
+impl ProviderDetail {
+    /// Whether the wallet announced the reverse-DNS identifier `expected`
+    ///
+    /// Compared case-insensitively. The rdns is self-declared, so a match
+    /// only means something if no other provider claims the same rdns; check
+    /// [`duplicate_rdns`] on the discovery results as well.
+    pub fn verify_rdns(&self, expected: &str) -> bool {
+        self.info.rdns.eq_ignore_ascii_case(expected)
+    }
+
+    /// Read the `detail` of an `eip6963:announceProvider` event
+    fn from_js(detail: &JsValue) -> Option<Self> {
+        let info = js_sys::Reflect::get(detail, &"info".into()).ok()?;
+        let provider = js_sys::Reflect::get(detail, &"provider".into()).ok()?;
+        if provider.is_null() || provider.is_undefined() {
+            return None;
+        }
+
+        Some(Self {
+            info: serde_wasm_bindgen::from_value(info).ok()?,
+            provider,
+        })
+    }
+}
+
+/// Discover the wallets installed in the browser (EIP-6963)
+///
+/// Dispatches `eip6963:requestProvider` and collects the announcements
+/// received within `wait`, in announcement order and deduplicated by UUID.
+/// Providers sharing an rdns are all kept (see [`duplicate_rdns`]).
+/// Wallets answer synchronously in practice, so a short wait (~100ms) is
+/// enough.
+pub async fn discover_providers(wait: Duration) -> Vec<ProviderDetail> {
+    let providers = Rc::new(RefCell::new(Vec::<ProviderDetail>::new()));
+
+    let announced = providers.clone();
+    let subscription = on_provider_announced(move |detail| {
+        let mut providers = announced.borrow_mut();
+        if !providers.iter().any(|p| p.info.uuid == detail.info.uuid) {
+            providers.push(detail);
+        }
+    });
+    sleep(wait).await;
+    drop(subscription);
+
+    let providers = providers.borrow().clone();
+    for rdns in duplicate_rdns(&providers) {
+        tracing::warn!(
+            "Several EIP-6963 providers claim rdns {}, possible spoofing",
+            rdns
+        );
+    }
+    providers
+}
+
+/// Reverse-DNS identifiers claimed by more than one of `providers`
+///
+/// Each wallet should announce a unique rdns, so a duplicate is a sign of a
+/// wallet impersonating another. [`discover_providers`] keeps every provider
+/// and logs a warning for each duplicate, leaving the decision (refuse, or
+/// ask the user to pick) to the dapp. Compared case-insensitively and
+/// returned lowercase, in first-announcement order.
+pub fn duplicate_rdns(providers: &[ProviderDetail]) -> Vec<String> {
+    let mut duplicates = Vec::new();
+    for (i, provider) in providers.iter().enumerate() {
+        let rdns = provider.info.rdns.to_ascii_lowercase();
+        if !duplicates.contains(&rdns)
+            && providers[i + 1..]
+                .iter()
+                .any(|other| other.verify_rdns(&rdns))
+        {
+            duplicates.push(rdns);
+        }
+    }
+    duplicates
+}
+
+/// Listen for EIP-6963 announcements for as long as the guard is kept
+///
+/// `callback` is called for every wallet already installed (their answer to
+/// the `eip6963:requestProvider` dispatched here) and then for any wallet
+/// announced later, e.g. one the user enables mid-session, so wallet pickers
+/// can stay current. Wallets may announce themselves more than once;
+/// deduplicate on [`ProviderInfo::uuid`]. The listener is removed when the
+/// returned [`AnnouncementSubscription`] is dropped.
+pub fn on_provider_announced(
+    mut callback: impl FnMut(ProviderDetail) + 'static,
+) -> AnnouncementSubscription {
+    let callback = Closure::<dyn FnMut(JsValue)>::new(move |detail: JsValue| {
+        match ProviderDetail::from_js(&detail) {
+            Some(detail) => callback(detail),
+            None => tracing::debug!("Ignoring malformed EIP-6963 announcement"),
+        }
+    });
+    let handler = eip6963_listen(callback.as_ref().unchecked_ref());
+
+    AnnouncementSubscription {
+        handler,
+        _callback: callback,
+    }
+}
+
+/// Guard for an EIP-6963 announcement listener, removing it when dropped
+#[derive(Debug)]
+pub struct AnnouncementSubscription {
+    handler: JsValue,
+    _callback: Closure<dyn FnMut(JsValue)>,
+}
+
+impl Drop for AnnouncementSubscription {
+    fn drop(&mut self) {
+        eip6963_unlisten(&self.handler);
+    }
+}
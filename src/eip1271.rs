@@ -0,0 +1,131 @@
+//! Contract account detection and signature verification (EIP-1271)
+
+use alloy_primitives::{eip191_hash_message, fixed_bytes, Address, FixedBytes, Signature, B256};
+use alloy_rpc_types_eth::BlockId;
+use alloy_sol_types::sol;
+
+use crate::error::{Result, WindowError};
+use crate::provider::WindowProvider;
+
+/// Value `isValidSignature(bytes32,bytes)` returns for a valid signature,
+/// its own function selector
+pub const ERC1271_MAGIC_VALUE: FixedBytes<4> = fixed_bytes!("1626ba7e");
+
+sol! {
+    interface IERC1271 {
+        function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4 magicValue);
+    }
+}
+
+impl WindowProvider {
+    /// Check a signature of a smart-contract wallet (Safe, Argent, ...)
+    ///
+    /// Contract wallets can't produce signatures that recover to their
+    /// address; instead the contract's `isValidSignature(hash, signature)`
+    /// decides, and returns [`ERC1271_MAGIC_VALUE`] if the signature is valid.
+    /// `hash` is what was signed, e.g. the EIP-191 hash of a message or the
+    /// EIP-712 signing hash of typed data.
+    ///
+    /// A revert or a result that isn't a `bytes4` (such as the empty result
+    /// of an account without code) counts as invalid.
+    pub async fn verify_erc1271(&self, signer: Address, hash: B256, sig: &[u8]) -> Result<bool> {
+        let call = IERC1271::isValidSignatureCall {
+            hash,
+            signature: sig.to_vec().into(),
+        };
+        match self.call_decoded(signer, call).await {
+            Ok(magic) => Ok(magic == ERC1271_MAGIC_VALUE),
+            Err(WindowError::ExecutionReverted { .. }) | Err(WindowError::Abi(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether `address` has code, i.e. is a smart-contract wallet
+    ///
+    /// Signatures of contract accounts are checked with
+    /// [`verify_erc1271`](Self::verify_erc1271) rather than by recovering the
+    /// signer. EIP-7702 delegated EOAs have code too, and sign either way.
+    pub async fn is_contract_account(&self, address: Address) -> Result<bool> {
+        let code = self.get_code_at(address, BlockId::latest()).await?;
+        Ok(!code.is_empty())
+    }
+
+    /// Check `signature` is `signer`'s `personal_sign` signature of `message`
+    ///
+    /// The signature is first checked by EOA recovery; if it doesn't recover
+    /// to `signer` and `signer` is a contract account, EIP-1271 decides, so
+    /// signatures of both plain and smart-contract wallets verify.
+    pub async fn verify_message(
+        &self,
+        signer: Address,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool> {
+        let recovered = Signature::try_from(signature)
+            .ok()
+            .and_then(|sig| sig.recover_address_from_msg(message).ok());
+        if recovered == Some(signer) {
+            return Ok(true);
+        }
+
+        if !self.is_contract_account(signer).await? {
+            return Ok(false);
+        }
+        self.verify_erc1271(signer, eip191_hash_message(message), signature)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{rpc_error, MockWallet};
+    use serde_json::{json, Value};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    /// `eth_call` result of `isValidSignature` returning `magic`
+    fn returns(magic: &str) -> Value {
+        json!(format!("0x{magic}{}", "0".repeat(56)))
+    }
+
+    async fn verify(result: std::result::Result<Value, Value>) -> Result<bool> {
+        let wallet = MockWallet::new(move |method, _| match method {
+            "eth_call" => result.clone(),
+            _ => Ok(Value::Null),
+        });
+        let provider = WindowProvider::from_transport(wallet.transport());
+        provider
+            .verify_erc1271(
+                Address::repeat_byte(0x11),
+                B256::repeat_byte(0x22),
+                &[0x33; 65],
+            )
+            .await
+    }
+
+    #[wasm_bindgen_test]
+    async fn accepts_the_magic_value() {
+        assert!(verify(Ok(returns("1626ba7e"))).await.unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    async fn rejects_other_values() {
+        assert!(!verify(Ok(returns("ffffffff"))).await.unwrap());
+        // Accounts without code return nothing
+        assert!(!verify(Ok(json!("0x"))).await.unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    async fn treats_reverts_as_invalid() {
+        let revert = rpc_error(3, "execution reverted");
+        assert!(!verify(Err(revert)).await.unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    async fn propagates_other_errors() {
+        let err = verify(Err(rpc_error(4200, "eth_call is not supported")))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WindowError::UnsupportedMethod(_)));
+    }
+}
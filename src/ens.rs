@@ -0,0 +1,148 @@
+//! ENS name resolution through the wallet provider
+
+use alloy_primitives::{keccak256, B256};
+#[cfg(target_arch = "wasm32")]
+use {
+    crate::error::Result,
+    crate::provider::WindowProvider,
+    alloy_primitives::{address, Address},
+    alloy_sol_types::{sol, SolCall},
+};
+
+/// ENS registry address, identical on every chain ENS is deployed to
+#[cfg(target_arch = "wasm32")]
+const ENS_REGISTRY: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+/// Chains with an ENS deployment: mainnet, Sepolia and Holesky
+#[cfg(target_arch = "wasm32")]
+const ENS_CHAINS: &[u64] = &[1, 11155111, 17000];
+
+#[cfg(target_arch = "wasm32")]
+sol! {
+    interface IEnsRegistry {
+        function resolver(bytes32 node) external view returns (address);
+    }
+
+    interface IEnsResolver {
+        function addr(bytes32 node) external view returns (address);
+        function name(bytes32 node) external view returns (string);
+    }
+}
+
+/// Compute the ENS namehash of a name (EIP-137)
+///
+/// Labels are lowercased but otherwise not normalized; callers accepting
+/// user input should apply full ENSIP-15 normalization first.
+pub fn namehash(name: &str) -> B256 {
+    if name.is_empty() {
+        return B256::ZERO;
+    }
+
+    name.rsplit('.').fold(B256::ZERO, |node, label| {
+        let label_hash = keccak256(label.to_lowercase().as_bytes());
+        keccak256([node.as_slice(), label_hash.as_slice()].concat())
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WindowProvider {
+    /// Resolve an ENS name (e.g. `vitalik.eth`) to an address
+    ///
+    /// Returns `None` if the name has no resolver or address, or if the wallet
+    /// is connected to a chain without ENS.
+    pub async fn lookup_address(&self, name: &str) -> Result<Option<Address>> {
+        if !self.has_ens().await? {
+            return Ok(None);
+        }
+
+        let node = namehash(name);
+        let Some(resolver) = self.resolver(node).await? else {
+            return Ok(None);
+        };
+
+        let output = self
+            .eth_call(
+                resolver,
+                IEnsResolver::addrCall { node }.abi_encode().into(),
+            )
+            .await?;
+        let address = IEnsResolver::addrCall::abi_decode_returns(&output)?;
+
+        Ok(Some(address).filter(|address| !address.is_zero()))
+    }
+
+    /// Look up the primary ENS name of an address
+    ///
+    /// The reverse record is only returned if the name resolves back to the
+    /// same address, since anyone can set an arbitrary reverse record.
+    pub async fn reverse_lookup(&self, address: Address) -> Result<Option<String>> {
+        if !self.has_ens().await? {
+            return Ok(None);
+        }
+
+        let node = namehash(&format!("{:x}.addr.reverse", address));
+        let Some(resolver) = self.resolver(node).await? else {
+            return Ok(None);
+        };
+
+        let output = self
+            .eth_call(
+                resolver,
+                IEnsResolver::nameCall { node }.abi_encode().into(),
+            )
+            .await?;
+        let name = IEnsResolver::nameCall::abi_decode_returns(&output)?;
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        // Verify the forward resolution matches
+        Ok(match self.lookup_address(&name).await? {
+            Some(resolved) if resolved == address => Some(name),
+            _ => None,
+        })
+    }
+
+    /// Whether the connected chain has an ENS deployment
+    async fn has_ens(&self) -> Result<bool> {
+        Ok(ENS_CHAINS.contains(&self.chain_id().await?))
+    }
+
+    /// Get the resolver of a node from the ENS registry
+    async fn resolver(&self, node: B256) -> Result<Option<Address>> {
+        let output = self
+            .eth_call(
+                ENS_REGISTRY,
+                IEnsRegistry::resolverCall { node }.abi_encode().into(),
+            )
+            .await?;
+        let resolver = IEnsRegistry::resolverCall::abi_decode_returns(&output)?;
+
+        Ok(Some(resolver).filter(|resolver| !resolver.is_zero()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+
+    #[test]
+    fn computes_namehashes() {
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("eth"),
+            b256!("93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+        );
+        assert_eq!(
+            namehash("foo.eth"),
+            b256!("de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+        );
+    }
+
+    #[test]
+    fn lowercases_labels() {
+        assert_eq!(namehash("Foo.ETH"), namehash("foo.eth"));
+        assert_ne!(namehash("foo.eth"), namehash("bar.eth"));
+    }
+}
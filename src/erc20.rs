@@ -0,0 +1,130 @@
+//! ERC-20 token metadata and balance reads
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::{sol, SolCall};
+
+use crate::error::{Result, WindowError};
+use crate::events::Subscription;
+use crate::provider::WindowProvider;
+use crate::transport::WindowTransport;
+use crate::units::format_token_amount;
+
+sol! {
+    interface IERC20 {
+        function name() external view returns (string);
+        function symbol() external view returns (string);
+        function decimals() external view returns (uint8);
+        function balanceOf(address account) external view returns (uint256);
+    }
+}
+
+/// ERC-20 token metadata and an account's balance
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenInfo {
+    /// Token name
+    pub name: String,
+    /// Token symbol
+    pub symbol: String,
+    /// Token decimals
+    pub decimals: u8,
+    /// Raw balance in the token's smallest unit
+    pub balance: U256,
+    /// Balance scaled by `decimals`, e.g. `"1.500000"` for 1.5 USDC
+    pub formatted_balance: String,
+}
+
+/// Decimals of tokens on the current chain, shared by all clones of a provider
+///
+/// Cleared on `chainChanged`, since the same address can be a different
+/// token (or no token) on another chain.
+#[derive(Clone, Debug)]
+pub(crate) struct DecimalsCache {
+    decimals: Rc<RefCell<HashMap<Address, u8>>>,
+    _listener: Rc<Subscription>,
+}
+
+impl DecimalsCache {
+    /// Create an empty cache cleared by the transport's `chainChanged` events
+    pub(crate) fn new(transport: &WindowTransport) -> Self {
+        let decimals = Rc::new(RefCell::new(HashMap::new()));
+
+        let listener_decimals = decimals.clone();
+        let listener = transport.on_chain_changed(move |_| {
+            listener_decimals.borrow_mut().clear();
+        });
+
+        Self {
+            decimals,
+            _listener: Rc::new(listener),
+        }
+    }
+
+    /// Cached decimals of `token`, if fetched since the last chain change
+    fn get(&self, token: Address) -> Option<u8> {
+        self.decimals.borrow().get(&token).copied()
+    }
+
+    /// Remember the decimals of `token`
+    fn insert(&self, token: Address, decimals: u8) {
+        self.decimals.borrow_mut().insert(token, decimals);
+    }
+}
+
+impl WindowProvider {
+    /// Read an ERC-20 token's name, symbol, decimals and `account`'s balance
+    ///
+    /// All four calls go through a single Multicall3 `eth_call`, so this
+    /// costs one wallet round-trip.
+    pub async fn erc20_info(&self, token: Address, account: Address) -> Result<TokenInfo> {
+        let calls = vec![
+            (token, IERC20::nameCall {}.abi_encode().into()),
+            (token, IERC20::symbolCall {}.abi_encode().into()),
+            (token, IERC20::decimalsCall {}.abi_encode().into()),
+            (token, IERC20::balanceOfCall { account }.abi_encode().into()),
+        ];
+        let [name, symbol, decimals, balance]: [_; 4] = self
+            .multicall(calls)
+            .await?
+            .try_into()
+            .map_err(|_| WindowError::Rpc("unexpected multicall result count".to_string()))?;
+
+        let decimals = IERC20::decimalsCall::abi_decode_returns(&decimals)?;
+        self.decimals_cache().insert(token, decimals);
+        let balance = IERC20::balanceOfCall::abi_decode_returns(&balance)?;
+        let formatted_balance = format_token_amount(balance, decimals)?;
+
+        Ok(TokenInfo {
+            name: IERC20::nameCall::abi_decode_returns(&name)?,
+            symbol: IERC20::symbolCall::abi_decode_returns(&symbol)?,
+            decimals,
+            balance,
+            formatted_balance,
+        })
+    }
+
+    /// Get a token's decimals, fetching them only on the first call per chain
+    pub async fn token_decimals(&self, token: Address) -> Result<u8> {
+        if let Some(decimals) = self.decimals_cache().get(token) {
+            return Ok(decimals);
+        }
+        let decimals = self.call_decoded(token, IERC20::decimalsCall {}).await?;
+        self.decimals_cache().insert(token, decimals);
+        Ok(decimals)
+    }
+
+    /// Format a raw token amount with the token's decimals
+    ///
+    /// Decimals come from [`token_decimals`](Self::token_decimals), so
+    /// formatting many balances of a token costs one `decimals()` call.
+    /// Formatted like [`TokenInfo::formatted_balance`], with every decimal
+    /// place: 1.5 USDC (`1_500_000` with 6 decimals) formats as `"1.500000"`.
+    /// Fails with [`WindowError::InvalidQuantity`] for tokens reporting more
+    /// than 77 decimals.
+    pub async fn format_token_balance(&self, token: Address, raw: U256) -> Result<String> {
+        format_token_amount(raw, self.token_decimals(token).await?)
+    }
+}
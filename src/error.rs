@@ -1,5 +1,12 @@
 //! Error types for window.ethereum interactions
 
+use std::time::Duration;
+
+use alloy_json_rpc::ErrorPayload;
+use alloy_primitives::Address;
+use alloy_sol_types::{Panic, Revert, SolError};
+use alloy_transport::TransportError;
+use serde_json::json;
 use thiserror::Error;
 
 /// Errors that can occur when interacting with window.ethereum
@@ -17,6 +24,62 @@ pub enum WindowError {
     #[error("RPC error: {0}")]
     Rpc(String),
 
+    /// The wallet does not support the requested method (EIP-1193 code 4200)
+    #[error("Unsupported method: {0}")]
+    UnsupportedMethod(String),
+
+    /// Invalid JSON was received by the node (JSON-RPC code -32700)
+    #[error("Parse error: {0}")]
+    ParseError(String),
+
+    /// The JSON sent is not a valid request object (JSON-RPC code -32600)
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// The method does not exist or is not available (JSON-RPC code -32601)
+    #[error("Method not found: {0}")]
+    MethodNotFound(String),
+
+    /// Invalid method parameters (JSON-RPC code -32602)
+    #[error("Invalid params: {0}")]
+    InvalidParams(String),
+
+    /// Internal JSON-RPC error (JSON-RPC code -32603)
+    #[error("Internal error: {0}")]
+    InternalError(String),
+
+    /// The wallet resolved with a malformed JSON-RPC response
+    ///
+    /// Reported to alloy as an internal error (-32603), since the request
+    /// itself was fine.
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    /// The call or transaction reverted during execution
+    #[error("Execution reverted: {message}")]
+    ExecutionReverted {
+        /// Revert message reported by the node
+        message: String,
+        /// Revert data if the node returned it, usually hex-encoded
+        data: Option<String>,
+    },
+
+    /// Structured error object with a code outside the standard ranges
+    #[error("Provider error {code}: {message}")]
+    Provider {
+        /// Error code reported by the wallet
+        code: i64,
+        /// Error message reported by the wallet
+        message: String,
+    },
+
+    /// The wallet or its node is throttling requests (EIP-1474 code -32005)
+    #[error("Rate limited")]
+    RateLimited {
+        /// How long to wait before retrying, if the wallet said so
+        retry_after: Option<Duration>,
+    },
+
     /// JavaScript interop error
     #[error("JS error: {0}")]
     Js(String),
@@ -29,6 +92,10 @@ pub enum WindowError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// ABI encoding/decoding error
+    #[error("ABI error: {0}")]
+    Abi(#[from] alloy_sol_types::Error),
+
     /// Generic serialization error
     #[error("Serialization failed")]
     SerializationError,
@@ -37,24 +104,370 @@ pub enum WindowError {
     #[error("Invalid address: {0}")]
     InvalidAddress(String),
 
+    /// Invalid hex quantity
+    #[error("Invalid quantity: {0}")]
+    InvalidQuantity(String),
+
+    /// Icon or image URI the wallet would not accept
+    #[error("Invalid image: {0}")]
+    InvalidImage(String),
+
+    /// EIP-712 typed data is malformed
+    #[error("Invalid typed data: {0}")]
+    InvalidTypedData(String),
+
     /// Invalid signature format
     #[error("Invalid signature: {0}")]
     InvalidSignature(String),
 
+    /// The wallet is connected to a different chain than required
+    #[error("Chain mismatch: expected chain {expected}, wallet is on chain {actual}")]
+    ChainMismatch {
+        /// Chain ID the dapp requires
+        expected: u64,
+        /// Chain ID the wallet is connected to
+        actual: u64,
+    },
+
+    /// The active account is watch-only and can't sign
+    #[error("Account is watch-only and cannot sign")]
+    WatchOnlyAccount,
+
+    /// A request in a sequence failed; earlier requests succeeded
+    #[error("Request {index} failed: {source}")]
+    SequenceFailed {
+        /// Index of the failed request
+        index: usize,
+        /// Error of the failed request
+        source: Box<WindowError>,
+    },
+
+    /// The transaction sender is not the wallet's active account
+    #[error(
+        "Account mismatch: transaction is from {expected}, wallet's active account is {actual}"
+    )]
+    AccountMismatch {
+        /// Sender set on the transaction
+        expected: Address,
+        /// Account currently active in the wallet
+        actual: Address,
+    },
+
+    /// The provider emitted a 4900 `disconnect` and hasn't reconnected
+    #[error("Provider disconnected: {0}")]
+    Disconnected(String),
+
+    /// The wallet resolved a transaction submission without a hash
+    ///
+    /// The transaction may or may not have been sent; the user should check
+    /// their wallet's activity.
+    #[error("Wallet returned no transaction hash - transaction state unknown")]
+    NoTransactionHash,
+
+    /// The wallet didn't answer in time
+    #[error("Timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// The wallet refused to sign a message because of its size
+    ///
+    /// A signature can't be split across requests, so the payload has to be
+    /// shrunk (e.g. by signing its hash) to be signed by this wallet.
+    #[error(
+        "Message too large for the wallet to sign{}",
+        .limit.map(|limit| format!(" (limit {} bytes)", limit)).unwrap_or_default()
+    )]
+    PayloadTooLarge {
+        /// Size limit in bytes, if the wallet stated it
+        limit: Option<usize>,
+    },
+
+    /// Params nested deeper than the transport's limit
+    #[error("Params nested deeper than {0} levels")]
+    DepthLimitExceeded(usize),
+
     /// No accounts returned from wallet
     #[error("No accounts available")]
     NoAccounts,
 }
 
+impl WindowError {
+    /// Get the EIP-1193 / JSON-RPC error code this error was classified from
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            WindowError::UserRejected => Some(4001),
+            WindowError::UnsupportedMethod(_) => Some(4200),
+            WindowError::ParseError(_) => Some(-32700),
+            WindowError::InvalidRequest(_) => Some(-32600),
+            WindowError::MethodNotFound(_) => Some(-32601),
+            WindowError::InvalidParams(_) => Some(-32602),
+            WindowError::InternalError(_) | WindowError::InvalidResponse(_) => Some(-32603),
+            WindowError::ExecutionReverted { .. } => Some(3),
+            WindowError::RateLimited { .. } => Some(-32005),
+            WindowError::Provider { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Decode the revert reason of an [`ExecutionReverted`](WindowError::ExecutionReverted) error
+    ///
+    /// Standard `Error(string)` reverts yield the embedded message and
+    /// `Panic(uint256)` reverts a description of the panic code, e.g.
+    /// `panic: arithmetic underflow or overflow (0x11)`. For custom errors the
+    /// `0x`-prefixed 4-byte selector is returned, which can be looked up in
+    /// the contract ABI. `None` if there is no revert data.
+    pub fn revert_reason(&self) -> Option<String> {
+        let data = self.revert_data()?;
+        let selector = data.get(..4)?;
+
+        if selector == Revert::SELECTOR {
+            Revert::abi_decode(&data).ok().map(|revert| revert.reason)
+        } else if let Some(panic) = self.revert_panic() {
+            Some(panic.to_string())
+        } else {
+            Some(format!("0x{}", hex::encode(selector)))
+        }
+    }
+
+    /// Decode a Solidity `Panic(uint256)` revert (Solidity 0.8+)
+    ///
+    /// Panics are raised by the compiler for failed `assert`s (0x01),
+    /// arithmetic overflow (0x11), division by zero (0x12), out-of-bounds
+    /// array access (0x32) and similar. [`Panic::kind`] maps the code to a
+    /// [`PanicKind`](alloy_sol_types::PanicKind) to branch on; `None` if the
+    /// error isn't a panic revert.
+    pub fn revert_panic(&self) -> Option<Panic> {
+        let data = self.revert_data()?;
+        if data.get(..4)? != Panic::SELECTOR {
+            return None;
+        }
+        Panic::abi_decode(&data).ok()
+    }
+
+    /// Reinterpret a wallet's message-size rejection as [`WindowError::PayloadTooLarge`]
+    ///
+    /// Wallets have no code for oversized messages and report them as a
+    /// generic error, so this goes by the wording; other errors are returned
+    /// unchanged.
+    pub(crate) fn into_payload_too_large(self) -> Self {
+        let limit = match &self {
+            WindowError::Provider { message, .. }
+            | WindowError::InvalidParams(message)
+            | WindowError::InvalidRequest(message)
+            | WindowError::InternalError(message)
+            | WindowError::Rpc(message)
+            | WindowError::Js(message)
+                if is_size_limit_message(message) =>
+            {
+                parse_size_limit(message)
+            }
+            _ => return self,
+        };
+        WindowError::PayloadTooLarge { limit }
+    }
+
+    /// Hex-decoded revert data of an [`ExecutionReverted`](WindowError::ExecutionReverted) error
+    fn revert_data(&self) -> Option<Vec<u8>> {
+        let WindowError::ExecutionReverted {
+            data: Some(data), ..
+        } = self
+        else {
+            return None;
+        };
+        hex::decode(data.trim_start_matches("0x")).ok()
+    }
+}
+
+/// Classify a wallet error from its extracted `code`, `message` and `data` fields
+///
+/// EIP-1193 provider codes (4001 user rejection, 4200 unsupported method),
+/// JSON-RPC 2.0 codes (-32700..-32603) and EIP-1474 server codes map to
+/// dedicated variants; any other code is kept in [`WindowError::Provider`].
+/// Without a code, the message is checked for user-rejection wording.
+pub fn classify_error(
+    code: Option<i64>,
+    message: Option<String>,
+    data: Option<String>,
+) -> WindowError {
+    let Some(code) = code else {
+        return match message {
+            Some(message) if is_watch_only_message(&message) => WindowError::WatchOnlyAccount,
+            Some(message)
+                if message.contains("User denied")
+                    || message.contains("rejected")
+                    || message.contains("User rejected") =>
+            {
+                WindowError::UserRejected
+            }
+            Some(message) => WindowError::Js(message),
+            None => WindowError::Js("Unknown error".to_string()),
+        };
+    };
+    let message = message.unwrap_or_default();
+
+    // Wallets have no dedicated code for watch-only accounts and usually
+    // report them as a generic rejection or unauthorized error
+    if is_watch_only_message(&message) {
+        return WindowError::WatchOnlyAccount;
+    }
+
+    // Throttling is reported as -32005 or, by some wallets, only in the message
+    if code == -32005 || is_rate_limit_message(&message) {
+        return WindowError::RateLimited {
+            retry_after: data.as_deref().and_then(parse_retry_after),
+        };
+    }
+
+    // Nodes report reverts as code 3 or inside the -32000 server range
+    if code == 3 || message.contains("execution reverted") {
+        return WindowError::ExecutionReverted { message, data };
+    }
+
+    match code {
+        // EIP-1193 provider errors
+        4001 => WindowError::UserRejected,
+        4200 => WindowError::UnsupportedMethod(message),
+        // JSON-RPC 2.0 standard errors
+        -32700 => WindowError::ParseError(message),
+        -32600 => WindowError::InvalidRequest(message),
+        -32601 => WindowError::MethodNotFound(message),
+        -32602 => WindowError::InvalidParams(message),
+        -32603 => WindowError::InternalError(message),
+        // EIP-1474: method not supported
+        -32004 => WindowError::UnsupportedMethod(message),
+        _ => WindowError::Provider { code, message },
+    }
+}
+
+/// Whether a wallet error message says requests are being throttled
+fn is_rate_limit_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("request rate") || message.contains("rate limit")
+}
+
+/// Read a retry hint from error data: plain seconds or a `retryAfter` field
+fn parse_retry_after(data: &str) -> Option<Duration> {
+    let seconds = match data.trim().parse::<f64>() {
+        Ok(seconds) => seconds,
+        Err(_) => {
+            let data: serde_json::Value = serde_json::from_str(data).ok()?;
+            let hint = data.get("retryAfter").or_else(|| data.get("retry_after"))?;
+            hint.as_f64()
+                .or_else(|| hint.as_str().and_then(|s| s.parse().ok()))?
+        }
+    };
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Whether a wallet error message says a message is over a size limit
+///
+/// Only specific phrases count: broader words like "exceeds" also appear in
+/// unrelated errors ("gas required exceeds allowance").
+fn is_size_limit_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "too large",
+        "too big",
+        "message too long",
+        "data too long",
+        "payload size",
+        "message size",
+        "request entity",
+        "size limit",
+        "max length",
+        "maximum length",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// Words allowed between "limit" / "max" and the number that follows it
+const SIZE_LIMIT_FILLERS: &[&str] = &["of", "is", "size", "length", "allowed", "bytes"];
+
+/// Read the size limit from a size-limit message
+///
+/// Takes the number following "limit" or "max"/"maximum", e.g. `1024` from
+/// "payload size 2048 exceeds limit of 1024": the first number in the
+/// message is often the payload size instead. `None` if no number follows
+/// either word, or if different numbers do.
+fn parse_size_limit(message: &str) -> Option<usize> {
+    let message = message.to_lowercase();
+    let mut limits = ["limit", "max"].iter().flat_map(|keyword| {
+        message
+            .match_indices(keyword)
+            // Skip the rest of the word, as in "maximum" or "limits"
+            .map(|(i, keyword)| {
+                message[i + keyword.len()..].trim_start_matches(|c: char| c.is_ascii_alphabetic())
+            })
+            .filter_map(number_after)
+    });
+
+    let limit = limits.next()?;
+    limits.all(|other| other == limit).then_some(limit)
+}
+
+/// The number starting the first word of `text` that isn't a filler word,
+/// within the same clause
+fn number_after(text: &str) -> Option<usize> {
+    if text.trim_start().starts_with(',') {
+        return None;
+    }
+    text.split(|c: char| !c.is_ascii_alphanumeric() && c != ',')
+        .map(|word| word.trim_matches(','))
+        .filter(|word| !word.is_empty())
+        .find(|word| !SIZE_LIMIT_FILLERS.contains(word))
+        .filter(|word| word.starts_with(|c: char| c.is_ascii_digit()))?
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == ',')
+        .filter(char::is_ascii_digit)
+        .collect::<String>()
+        .parse()
+        .ok()
+}
+
+/// Whether a wallet error message says the account can't sign
+fn is_watch_only_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "watch-only",
+        "watch only",
+        "watch mode",
+        "read-only account",
+        "readonly account",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
 impl From<wasm_bindgen::JsValue> for WindowError {
     fn from(val: wasm_bindgen::JsValue) -> Self {
         // Try to extract meaningful error message
         if let Some(s) = val.as_string() {
-            // Check for user rejection
-            if s.contains("User denied") || s.contains("rejected") || s.contains("User rejected") {
-                return WindowError::UserRejected;
+            return classify_error(None, Some(s), None);
+        }
+
+        // Wallets reject with EIP-1193 error objects ({ code, message, data })
+        // or JS Error instances; read their fields rather than debug-printing
+        if val.is_object() {
+            let field = |name: &str| js_sys::Reflect::get(&val, &name.into()).ok();
+            let code = field("code").and_then(|c| c.as_f64()).map(|c| c as i64);
+            let message = field("message").and_then(|m| m.as_string());
+            let data = field("data").unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
+
+            // MetaMask wraps node errors as { code: -32603, data: { code, message, data } },
+            // so prefer the inner error when present
+            if data.is_object() && js_sys::Reflect::has(&data, &"code".into()).unwrap_or(false) {
+                return WindowError::from(data);
+            }
+
+            // Structured data (e.g. a retry hint) is passed on as JSON
+            let data = data.as_string().or_else(|| {
+                data.is_object()
+                    .then(|| js_sys::JSON::stringify(&data).ok()?.as_string())
+                    .flatten()
+            });
+            if code.is_some() || message.is_some() {
+                return classify_error(code, message, data);
             }
-            return WindowError::Js(s);
         }
 
         // Fallback to debug representation
@@ -62,5 +475,227 @@ impl From<wasm_bindgen::JsValue> for WindowError {
     }
 }
 
+impl From<WindowError> for TransportError {
+    /// Keep the code, message and data of wallet errors as a JSON-RPC error
+    /// response, so [`WindowError::try_from_transport`] can recover them
+    fn from(e: WindowError) -> Self {
+        match e.to_error_payload() {
+            Some(payload) => TransportError::ErrorResp(payload),
+            None => TransportError::local_usage_str(&e.to_string()),
+        }
+    }
+}
+
+impl WindowError {
+    /// Recover the wallet error behind an alloy [`TransportError`]
+    ///
+    /// Errors from this crate's transport reach alloy as JSON-RPC error
+    /// responses carrying the wallet's code, so they classify back into the
+    /// same variant: after a failed `provider.send_transaction(..)`, check for
+    /// `Some(WindowError::UserRejected)`. Returns `None` for errors that
+    /// didn't come from the wallet, such as serialization failures or errors
+    /// without a code.
+    pub fn try_from_transport(error: &TransportError) -> Option<WindowError> {
+        let payload = error.as_error_resp()?;
+        let data = payload.data.as_ref().map(|data| {
+            serde_json::from_str::<String>(data.get()).unwrap_or_else(|_| data.get().to_string())
+        });
+        Some(classify_error(
+            Some(payload.code),
+            Some(payload.message.to_string()),
+            data,
+        ))
+    }
+
+    /// JSON-RPC error payload for errors that carry a wallet code
+    pub(crate) fn to_error_payload(&self) -> Option<ErrorPayload> {
+        let code = self.code()?;
+        let (message, data) = match self {
+            WindowError::UnsupportedMethod(message)
+            | WindowError::ParseError(message)
+            | WindowError::InvalidRequest(message)
+            | WindowError::MethodNotFound(message)
+            | WindowError::InvalidParams(message)
+            | WindowError::InternalError(message)
+            | WindowError::InvalidResponse(message)
+            | WindowError::Provider { message, .. } => (message.clone(), None),
+            WindowError::ExecutionReverted { message, data } => {
+                (message.clone(), data.as_ref().map(|data| json!(data)))
+            }
+            WindowError::RateLimited {
+                retry_after: Some(retry_after),
+            } => (
+                self.to_string(),
+                Some(json!({ "retryAfter": retry_after.as_secs_f64() })),
+            ),
+            _ => (self.to_string(), None),
+        };
+
+        Some(ErrorPayload {
+            code,
+            message: message.into(),
+            data: data.and_then(|data| serde_json::value::to_raw_value(&data).ok()),
+        })
+    }
+}
+
 /// Result type alias for window.ethereum operations
 pub type Result<T> = std::result::Result<T, WindowError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(code: i64) -> WindowError {
+        classify_error(Some(code), Some("boom".to_string()), None)
+    }
+
+    #[test]
+    fn classifies_provider_codes() {
+        assert!(matches!(classify(4001), WindowError::UserRejected));
+        assert!(matches!(classify(4200), WindowError::UnsupportedMethod(m) if m == "boom"));
+    }
+
+    #[test]
+    fn classifies_json_rpc_codes() {
+        assert!(matches!(classify(-32700), WindowError::ParseError(_)));
+        assert!(matches!(classify(-32600), WindowError::InvalidRequest(_)));
+        assert!(matches!(classify(-32601), WindowError::MethodNotFound(_)));
+        assert!(matches!(classify(-32602), WindowError::InvalidParams(_)));
+        assert!(matches!(classify(-32603), WindowError::InternalError(_)));
+    }
+
+    #[test]
+    fn classifies_rate_limit() {
+        assert!(matches!(
+            classify(-32005),
+            WindowError::RateLimited { retry_after: None }
+        ));
+        let error = classify_error(Some(-32005), None, Some(r#"{"retryAfter":2}"#.to_string()));
+        assert!(matches!(
+            error,
+            WindowError::RateLimited { retry_after: Some(d) } if d == Duration::from_secs(2)
+        ));
+    }
+
+    #[test]
+    fn classifies_revert() {
+        let error = classify_error(
+            Some(3),
+            Some("execution reverted".to_string()),
+            Some("0x".to_string()),
+        );
+        assert!(matches!(
+            error,
+            WindowError::ExecutionReverted { data: Some(d), .. } if d == "0x"
+        ));
+    }
+
+    #[test]
+    fn keeps_unknown_codes() {
+        assert!(matches!(
+            classify(-31999),
+            WindowError::Provider { code: -31999, message } if message == "boom"
+        ));
+        for code in [
+            4001, 4200, -32700, -32600, -32601, -32602, -32603, -32005, 3, -31999,
+        ] {
+            assert_eq!(classify(code).code(), Some(code));
+        }
+    }
+
+    #[test]
+    fn classifies_without_code() {
+        let error = classify_error(None, Some("User rejected the request.".to_string()), None);
+        assert!(matches!(error, WindowError::UserRejected));
+        assert!(matches!(
+            classify_error(None, None, None),
+            WindowError::Js(_)
+        ));
+    }
+
+    #[test]
+    fn detects_size_limit_messages() {
+        assert!(is_size_limit_message("Request entity too large"));
+        assert!(is_size_limit_message("Payload size exceeds the limit"));
+        assert!(!is_size_limit_message("gas required exceeds allowance (0)"));
+        assert!(!is_size_limit_message(
+            "insufficient funds: amount exceeds balance"
+        ));
+    }
+
+    #[test]
+    fn parses_size_limits() {
+        assert_eq!(
+            parse_size_limit("payload size 2048 exceeds limit of 1024"),
+            Some(1024)
+        );
+        assert_eq!(
+            parse_size_limit("Message too long (max 65,536 bytes)"),
+            Some(65536)
+        );
+        assert_eq!(
+            parse_size_limit("message too long, maximum length is 4096"),
+            Some(4096)
+        );
+        assert_eq!(parse_size_limit("Limit: 100. Payload too large"), Some(100));
+        // No number after the keyword, or conflicting ones
+        assert_eq!(parse_size_limit("Message of 2048 bytes is too large"), None);
+        assert_eq!(
+            parse_size_limit("too large for the limit, 2048 > 1024"),
+            None
+        );
+        assert_eq!(parse_size_limit("limit 1024, max 2048"), None);
+    }
+
+    #[test]
+    fn maps_size_rejections_to_payload_too_large() {
+        let error = WindowError::Js("Request entity too large, limit 1024".to_string());
+        assert!(matches!(
+            error.into_payload_too_large(),
+            WindowError::PayloadTooLarge { limit: Some(1024) }
+        ));
+        let error = WindowError::Js("gas required exceeds allowance".to_string());
+        assert!(matches!(error.into_payload_too_large(), WindowError::Js(_)));
+    }
+
+    fn reverted(data: &[u8]) -> WindowError {
+        WindowError::ExecutionReverted {
+            message: "execution reverted".to_string(),
+            data: Some(format!("0x{}", hex::encode(data))),
+        }
+    }
+
+    #[test]
+    fn decodes_revert_reasons() {
+        let revert = Revert::from("insufficient allowance").abi_encode();
+        assert_eq!(
+            reverted(&revert).revert_reason().as_deref(),
+            Some("insufficient allowance")
+        );
+        assert_eq!(
+            reverted(&[0xde, 0xad, 0xbe, 0xef, 0x00])
+                .revert_reason()
+                .as_deref(),
+            Some("0xdeadbeef")
+        );
+        assert_eq!(reverted(&[]).revert_reason(), None);
+        assert_eq!(WindowError::UserRejected.revert_reason(), None);
+    }
+
+    #[test]
+    fn decodes_panic_codes() {
+        let panic = Panic {
+            code: alloy_primitives::U256::from(0x11),
+        }
+        .abi_encode();
+        let error = reverted(&panic);
+        assert_eq!(
+            error.revert_panic().and_then(|panic| panic.kind()),
+            Some(alloy_sol_types::PanicKind::UnderOverflow)
+        );
+        assert!(error.revert_reason().unwrap().contains("0x11"));
+        let revert = Revert::from("nope").abi_encode();
+        assert!(reverted(&revert).revert_panic().is_none());
+    }
+}
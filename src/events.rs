@@ -0,0 +1,82 @@
+//! EIP-1193 event subscriptions on window.ethereum
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::js_bindings::{ethereum_on, ethereum_remove_listener};
+
+/// Guard for an event listener registered on window.ethereum
+///
+/// The listener is removed when the guard is dropped.
+#[derive(Debug)]
+pub struct Subscription {
+    ethereum: JsValue,
+    event: String,
+    callback: Closure<dyn FnMut(JsValue)>,
+}
+
+impl Subscription {
+    /// Register `callback` for `event` on the given provider
+    pub(crate) fn new(
+        ethereum: &JsValue,
+        event: &str,
+        callback: impl FnMut(JsValue) + 'static,
+    ) -> Self {
+        let callback = Closure::<dyn FnMut(JsValue)>::new(callback);
+        ethereum_on(ethereum, event, callback.as_ref().unchecked_ref());
+
+        Self {
+            ethereum: ethereum.clone(),
+            event: event.to_string(),
+            callback,
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        ethereum_remove_listener(
+            &self.ethereum,
+            &self.event,
+            self.callback.as_ref().unchecked_ref(),
+        );
+    }
+}
+
+/// Payload of an EIP-1193 `message` event
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProviderMessage {
+    /// Notification for an `eth_subscribe` subscription
+    EthSubscription {
+        /// Subscription ID returned by `eth_subscribe`
+        subscription: String,
+        /// Notification payload
+        result: Value,
+    },
+    /// Any other (wallet-specific) message
+    Other {
+        /// The message `type` field
+        kind: String,
+        /// The message `data` field
+        data: Value,
+    },
+}
+
+impl ProviderMessage {
+    /// Parse a `{ type, data }` message payload
+    pub(crate) fn from_value(message: Value) -> Self {
+        let kind = message["type"].as_str().unwrap_or_default().to_string();
+        let data = message.get("data").cloned().unwrap_or(Value::Null);
+
+        if kind == "eth_subscription" {
+            if let Some(subscription) = data["subscription"].as_str() {
+                return ProviderMessage::EthSubscription {
+                    subscription: subscription.to_string(),
+                    result: data.get("result").cloned().unwrap_or(Value::Null),
+                };
+            }
+        }
+
+        ProviderMessage::Other { kind, data }
+    }
+}
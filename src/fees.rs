@@ -0,0 +1,245 @@
+//! EIP-1559 fee suggestions from `eth_feeHistory` and affordability checks
+
+use alloy_primitives::U256;
+use alloy_rpc_types_eth::FeeHistory;
+#[cfg(target_arch = "wasm32")]
+use {
+    crate::accounts::parse_accounts,
+    crate::error::{Result, WindowError},
+    crate::provider::WindowProvider,
+    crate::units::to_hex_quantity,
+    alloy_primitives::Address,
+    alloy_rpc_types_eth::TransactionRequest,
+    serde_json::json,
+};
+
+/// Number of recent blocks sampled by [`WindowProvider::suggest_fees`]
+#[cfg(target_arch = "wasm32")]
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// Reward percentile requested per block, used for the priority fee
+const REWARD_PERCENTILE: f64 = 50.0;
+
+/// Percentile of the sampled base fees used as the expected base fee
+const BASE_FEE_PERCENTILE: f64 = 90.0;
+
+/// Suggested EIP-1559 fees
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// Suggested `maxFeePerGas`
+    pub max_fee_per_gas: U256,
+    /// Suggested `maxPriorityFeePerGas`
+    pub max_priority_fee_per_gas: U256,
+}
+
+/// Whether an account can pay for a transaction, from
+/// [`WindowProvider::check_affordable`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Affordability {
+    /// Whether the balance covers the worst-case cost
+    pub affordable: bool,
+    /// Worst-case cost in wei: value plus gas limit times max gas price
+    pub required: U256,
+    /// Balance of the sender in wei
+    pub balance: U256,
+}
+
+impl Affordability {
+    /// Amount missing from the balance, zero if affordable
+    pub fn shortfall(&self) -> U256 {
+        self.required.saturating_sub(self.balance)
+    }
+}
+
+impl FeeEstimate {
+    /// Build an estimate from a fee history, `None` if it has no usable samples
+    ///
+    /// The priority fee is the median of the per-block median rewards; the
+    /// max fee leaves room for the base fee to double from its 90th
+    /// percentile.
+    fn from_history(history: &FeeHistory) -> Option<Self> {
+        // Pre-EIP-1559 blocks report a zero base fee
+        let base_fee =
+            percentile(&history.base_fee_per_gas, BASE_FEE_PERCENTILE).filter(|fee| *fee > 0)?;
+        let rewards: Vec<u128> = history
+            .reward
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|block| block.first().copied())
+            .collect();
+        let priority_fee = percentile(&rewards, REWARD_PERCENTILE)?;
+
+        Some(Self {
+            max_fee_per_gas: U256::from(base_fee) * U256::from(2) + U256::from(priority_fee),
+            max_priority_fee_per_gas: U256::from(priority_fee),
+        })
+    }
+}
+
+/// Nearest-rank percentile of `values`, `None` when empty
+///
+/// `pct` is clamped to `0.0..=100.0`; the input doesn't need to be sorted.
+pub(crate) fn percentile(values: &[u128], pct: f64) -> Option<u128> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+
+    let rank = (pct.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.saturating_sub(1)])
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WindowProvider {
+    /// Suggest EIP-1559 fees based on recent blocks
+    ///
+    /// Uses `eth_feeHistory` over the last few blocks. If the wallet doesn't
+    /// support it (or the chain has no EIP-1559 history), falls back to
+    /// `eth_gasPrice` for both values.
+    ///
+    /// # Note
+    /// Most wallets override caller-supplied fees with their own estimate;
+    /// this is mainly useful for showing an expected cost before sending.
+    pub async fn suggest_fees(&self) -> Result<FeeEstimate> {
+        let history = self
+            .request::<FeeHistory>(
+                "eth_feeHistory",
+                json!([
+                    to_hex_quantity(U256::from(FEE_HISTORY_BLOCKS)),
+                    "latest",
+                    [REWARD_PERCENTILE]
+                ]),
+            )
+            .await;
+
+        match history {
+            Ok(history) => {
+                if let Some(estimate) = FeeEstimate::from_history(&history) {
+                    return Ok(estimate);
+                }
+            }
+            Err(e) => tracing::debug!("eth_feeHistory failed, falling back to eth_gasPrice: {}", e),
+        }
+
+        let gas_price: U256 = self.request("eth_gasPrice", json!([])).await?;
+        Ok(FeeEstimate {
+            max_fee_per_gas: gas_price,
+            max_priority_fee_per_gas: gas_price,
+        })
+    }
+
+    /// Check the sender can pay for a transaction before prompting the wallet
+    ///
+    /// Compares the sender's balance against the worst-case cost
+    /// `value + gas * price`, in exact integer arithmetic. The sender is
+    /// `tx.from` or the active account, and the gas limit is `tx.gas` or an
+    /// `eth_estimateGas` estimate. The price is `maxFeePerGas` for EIP-1559
+    /// transactions, `gasPrice` for legacy ones, and
+    /// [`suggest_fees`](Self::suggest_fees) when neither is set.
+    ///
+    /// # Note
+    /// Gas estimation itself fails for most transactions the sender can't
+    /// afford; that error is returned as-is.
+    pub async fn check_affordable(&self, tx: &TransactionRequest) -> Result<Affordability> {
+        let from = match tx.from {
+            Some(from) => from,
+            None => self.active_account().await?,
+        };
+        let tx = TransactionRequest {
+            from: Some(from),
+            ..tx.clone()
+        };
+
+        let gas = async {
+            match tx.gas {
+                Some(gas) => Ok(U256::from(gas)),
+                None => self.request("eth_estimateGas", json!([tx])).await,
+            }
+        };
+        let price = async {
+            match tx.max_fee_per_gas.or(tx.gas_price) {
+                Some(price) => Ok(U256::from(price)),
+                None => Ok(self.suggest_fees().await?.max_fee_per_gas),
+            }
+        };
+        let balance = self.request::<U256>("eth_getBalance", json!([from, "latest"]));
+        let (gas, price, balance) = futures::try_join!(gas, price, balance)?;
+
+        let required = gas
+            .saturating_mul(price)
+            .saturating_add(tx.value.unwrap_or_default());
+        Ok(Affordability {
+            affordable: balance >= required,
+            required,
+            balance,
+        })
+    }
+
+    /// Get the wallet's active account
+    async fn active_account(&self) -> Result<Address> {
+        let accounts = parse_accounts(&self.request("eth_accounts", json!([])).await?)?;
+        accounts.first().copied().ok_or(WindowError::NoAccounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(base_fees: &[u128], rewards: &[u128]) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: base_fees.to_vec(),
+            reward: Some(rewards.iter().map(|reward| vec![*reward]).collect()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn takes_nearest_rank_percentiles() {
+        let values = [5, 1, 4, 2, 3];
+        assert_eq!(percentile(&values, 0.0), Some(1));
+        assert_eq!(percentile(&values, 50.0), Some(3));
+        assert_eq!(percentile(&values, 90.0), Some(5));
+        assert_eq!(percentile(&values, 100.0), Some(5));
+        assert_eq!(percentile(&values, 150.0), Some(5));
+        assert_eq!(percentile(&[7], 50.0), Some(7));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn estimates_from_history() {
+        let estimate = FeeEstimate::from_history(&history(&[10, 20, 30], &[1, 3, 2])).unwrap();
+        assert_eq!(estimate.max_priority_fee_per_gas, U256::from(2));
+        assert_eq!(estimate.max_fee_per_gas, U256::from(30 * 2 + 2));
+    }
+
+    #[test]
+    fn rejects_unusable_history() {
+        assert_eq!(FeeEstimate::from_history(&history(&[0, 0], &[1, 1])), None);
+        assert_eq!(FeeEstimate::from_history(&history(&[10], &[])), None);
+        let no_rewards = FeeHistory {
+            base_fee_per_gas: vec![10],
+            ..Default::default()
+        };
+        assert_eq!(FeeEstimate::from_history(&no_rewards), None);
+    }
+
+    #[test]
+    fn computes_shortfall() {
+        let affordability = Affordability {
+            affordable: false,
+            required: U256::from(100),
+            balance: U256::from(40),
+        };
+        assert_eq!(affordability.shortfall(), U256::from(60));
+        let affordability = Affordability {
+            affordable: true,
+            required: U256::from(40),
+            balance: U256::from(100),
+        };
+        assert_eq!(affordability.shortfall(), U256::ZERO);
+    }
+}
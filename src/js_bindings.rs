@@ -0,0 +1,214 @@
+//! Inline JS bindings to window.ethereum shared by the transport and signer
+
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+/// Get the window.ethereum object and make requests to it
+///
+/// Also manages event listeners, probes the RPC URL and wallet version, waits
+/// on timers and discovers wallets (EIP-6963).
+#[wasm_bindgen(inline_js = r#"
+export function get_ethereum() {
+    // Some environments inject a throwing getter or Proxy as window.ethereum
+    try {
+        if (typeof window !== 'undefined' && window.ethereum) {
+            return window.ethereum;
+        }
+    } catch (e) {
+        return null;
+    }
+    return null;
+}
+
+let legacy_request_id = 0;
+
+export function ethereum_request(ethereum, method, params) {
+    if (typeof ethereum.request === 'function') {
+        return ethereum.request({ method, params });
+    }
+
+    // Pre-EIP-1193 providers, still found in some mobile dapp browsers
+    if (typeof ethereum.sendAsync === 'function') {
+        const payload = { jsonrpc: '2.0', id: ++legacy_request_id, method, params };
+        return new Promise((resolve, reject) => {
+            ethereum.sendAsync(payload, (error, response) => {
+                if (error) {
+                    reject(error);
+                } else if (response && response.error) {
+                    reject(response.error);
+                } else {
+                    resolve(response ? response.result : undefined);
+                }
+            });
+        });
+    }
+    if (typeof ethereum.send === 'function') {
+        return Promise.resolve()
+            .then(() => ethereum.send(method, params))
+            .then((response) => {
+                // Some return the bare result, others a JSON-RPC response
+                if (response === null || typeof response !== 'object' || !('jsonrpc' in response)) {
+                    return response;
+                }
+                if (response.error) {
+                    throw response.error;
+                }
+                return response.result;
+            });
+    }
+
+    return Promise.reject(
+        new Error('Provider implements none of request(), sendAsync() or send()')
+    );
+}
+
+export function ethereum_request_with_options(ethereum, method, params, options) {
+    if (typeof ethereum.request === 'function') {
+        return ethereum.request({ method, params }, options);
+    }
+    // Legacy methods have nowhere to put the options
+    return ethereum_request(ethereum, method, params);
+}
+
+export function ethereum_on(ethereum, event, callback) {
+    if (typeof ethereum.on === 'function') {
+        ethereum.on(event, callback);
+    }
+}
+
+export function ethereum_remove_listener(ethereum, event, callback) {
+    if (typeof ethereum.removeListener === 'function') {
+        ethereum.removeListener(event, callback);
+    }
+}
+
+export function ethereum_rpc_url(ethereum) {
+    // Non-standard properties some providers expose, most specific first
+    const probes = [
+        (p) => p.rpcUrl,
+        (p) => p.rpc && p.rpc.rpcUrl,
+        (p) => p.connection && p.connection.url,
+        (p) => p.host,
+    ];
+    for (const probe of probes) {
+        try {
+            const url = probe(ethereum);
+            if (typeof url === 'string' && /^https?:\/\//.test(url)) {
+                return url;
+            }
+        } catch (e) {
+            // Throwing getter, try the next property
+        }
+    }
+    return undefined;
+}
+
+export function ethereum_version(ethereum) {
+    // No standard location; wallets that expose a version use one of these
+    const fields = ['version', 'walletVersion', 'appVersion', 'providerVersion', '_version'];
+    for (const field of fields) {
+        try {
+            const version = ethereum[field];
+            if (typeof version === 'string' && version.length > 0) {
+                return version;
+            }
+            if (typeof version === 'number') {
+                return String(version);
+            }
+        } catch (e) {
+            // Throwing getter, try the next property
+        }
+    }
+    return undefined;
+}
+
+export function sleep(ms) {
+    return new Promise((resolve) => setTimeout(resolve, ms));
+}
+
+export function eip6963_listen(callback) {
+    const handler = (event) => callback(event.detail);
+    if (typeof window !== 'undefined') {
+        window.addEventListener('eip6963:announceProvider', handler);
+        window.dispatchEvent(new Event('eip6963:requestProvider'));
+    }
+    return handler;
+}
+
+export function eip6963_unlisten(handler) {
+    if (typeof window !== 'undefined') {
+        window.removeEventListener('eip6963:announceProvider', handler);
+    }
+}
+"#)]
+extern "C" {
+    #[wasm_bindgen(js_name = get_ethereum)]
+    pub(crate) fn get_ethereum() -> JsValue;
+
+    #[wasm_bindgen(js_name = ethereum_request)]
+    pub(crate) fn ethereum_request(
+        ethereum: &JsValue,
+        method: &str,
+        params: &JsValue,
+    ) -> js_sys::Promise;
+
+    #[wasm_bindgen(js_name = ethereum_request_with_options)]
+    pub(crate) fn ethereum_request_with_options(
+        ethereum: &JsValue,
+        method: &str,
+        params: &JsValue,
+        options: &JsValue,
+    ) -> js_sys::Promise;
+
+    #[wasm_bindgen(js_name = ethereum_on)]
+    pub(crate) fn ethereum_on(ethereum: &JsValue, event: &str, callback: &js_sys::Function);
+
+    #[wasm_bindgen(js_name = ethereum_remove_listener)]
+    pub(crate) fn ethereum_remove_listener(
+        ethereum: &JsValue,
+        event: &str,
+        callback: &js_sys::Function,
+    );
+
+    #[wasm_bindgen(js_name = ethereum_rpc_url)]
+    pub(crate) fn ethereum_rpc_url(ethereum: &JsValue) -> Option<String>;
+
+    #[wasm_bindgen(js_name = ethereum_version)]
+    pub(crate) fn ethereum_version(ethereum: &JsValue) -> Option<String>;
+
+    #[wasm_bindgen(js_name = sleep)]
+    fn sleep_promise(ms: u32) -> js_sys::Promise;
+
+    #[wasm_bindgen(js_name = eip6963_listen)]
+    pub(crate) fn eip6963_listen(callback: &js_sys::Function) -> JsValue;
+
+    #[wasm_bindgen(js_name = eip6963_unlisten)]
+    pub(crate) fn eip6963_unlisten(handler: &JsValue);
+}
+
+/// Wait for the given duration using the browser's `setTimeout`
+///
+/// Durations over ~24.8 days are capped to the longest delay `setTimeout` supports.
+pub(crate) async fn sleep(duration: Duration) {
+    // setTimeout fires immediately for delays over 2^31-1 ms (~24.8 days)
+    let ms = u32::try_from(duration.as_millis())
+        .unwrap_or(u32::MAX)
+        .min(i32::MAX as u32);
+    // setTimeout never rejects
+    let _ = JsFuture::from(sleep_promise(ms)).await;
+}
+
+/// Call the deprecated `ethereum.enable()`, `None` if the provider lacks it
+///
+/// Only used as a last resort for legacy providers that predate
+/// `eth_requestAccounts` (EIP-1102).
+pub(crate) async fn legacy_enable(ethereum: &JsValue) -> Option<Result<JsValue, JsValue>> {
+    let enable = js_sys::Reflect::get(ethereum, &"enable".into()).ok()?;
+    let enable = enable.dyn_into::<js_sys::Function>().ok()?;
+    let promise = match enable.call0(ethereum) {
+        Ok(promise) => js_sys::Promise::resolve(&promise),
+        Err(e) => return Some(Err(e)),
+    };
+    Some(JsFuture::from(promise).await)
+}
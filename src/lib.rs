@@ -2,17 +2,22 @@
 //!
 //! Alloy transport and signer implementations for browser wallets via `window.ethereum` (EIP-1193).
 //!
-//! This crate provides minimal, zero-dependency bridge between Alloy and browser-based Web3 wallets
-//! like MetaMask, Rabby, Coinbase Wallet, etc.
+//! This crate bridges Alloy and browser-based Web3 wallets like MetaMask, Rabby, Coinbase Wallet,
+//! etc., smoothing over the differences between wallets along the way.
 //!
 //! ## Features
 //!
-//! - **WindowTransport**: Implements Alloy's `Transport` trait to route RPC calls through `window.ethereum`
+//! - **WindowTransport**: Implements Alloy's `Transport` trait to route RPC calls through `window.ethereum`,
+//!   normalizing params and results for wallets that deviate from the spec
+//! - **WindowProvider**: Typed helpers over `WindowTransport` for common wallet flows (transaction lookups,
+//!   send-and-watch, fee suggestions, ERC-20 and ENS lookups, chain switching)
 //! - **WindowSigner**: Implements Alloy's `Signer` trait for message signing via `personal_sign` and EIP-712 typed data signing
+//! - **Wallet Events**: Connection state, account and chain change streams, and EIP-6963 wallet discovery
 //! - **WASM Compatible**: Designed specifically for use in browser environments
 //! - **Transaction Support**: Send transactions via `eth_sendTransaction` - browser wallet handles signing
 //! - **EIP-712 Support** (with `eip712` feature): Sign structured data using `eth_signTypedData_v4`
-//! - **Minimal Code**: ~200 lines of well-documented code
+//! - **Dioxus Integration** (with `dioxus` feature): Share a `WindowProvider` through Dioxus context
+//! - **Typed Errors**: Wallet and JSON-RPC error codes mapped to [`WindowError`] variants
 //!
 //! ## Example - Read-only Provider
 //!
@@ -62,18 +67,119 @@
 //! ## Note on Transaction Signing
 //!
 //! Browser wallets use `eth_sendTransaction` which signs AND broadcasts transactions in a single call.
-//! They don't support `eth_sign` for security reasons. Therefore:
+//! Most of them have disabled `eth_sign` for security reasons. Therefore:
 //! - `WindowSigner` implements message signing via `personal_sign` for EIP-191 signed messages
 //! - `WindowSigner` implements EIP-712 typed data signing via `eth_signTypedData_v4` (with `eip712` feature)
-//! - `WindowSigner` does NOT support `eth_sign` (deprecated and unsupported by wallets)
+//! - `WindowSigner::sign_hash` uses `eth_sign`, which most wallets have disabled; it returns
+//!   `UnsupportedOperation` when the wallet rejects the method
 //! - `WindowSigner` does NOT implement `TxSigner` or `NetworkWallet`
 //! - To send transactions, use `provider.send_transaction()` directly (no wallet attachment needed)
 //! - The `WindowTransport` automatically routes transaction requests through the browser wallet
+//! - For `eth_sendTransaction` and `eth_estimateGas`, numeric `gas`, `gasPrice`, `maxFeePerGas`,
+//!   `maxPriorityFeePerGas`, `value` and `nonce` fields are sent as `0x`-prefixed hex quantities
+//!   without leading zeros (see [`to_hex_quantity`])
 
+// Outside the browser only the pure request/response logic is built, for
+// host-side unit tests, and most of it is unreachable without the bridge
+#![cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+
+mod accounts;
+mod bigint;
+#[cfg(target_arch = "wasm32")]
+mod builder;
+#[cfg(target_arch = "wasm32")]
+mod capabilities;
+#[cfg(target_arch = "wasm32")]
+mod connection;
+#[cfg(all(target_arch = "wasm32", feature = "dioxus"))]
+mod dioxus;
+#[cfg(target_arch = "wasm32")]
+mod discovery;
+#[cfg(target_arch = "wasm32")]
+mod eip1271;
+mod ens;
+#[cfg(target_arch = "wasm32")]
+mod erc20;
 mod error;
+#[cfg(target_arch = "wasm32")]
+mod events;
+mod fees;
+#[cfg(target_arch = "wasm32")]
+mod js_bindings;
+mod limit;
+mod logging;
+#[cfg(all(test, target_arch = "wasm32"))]
+mod mock;
+#[cfg(target_arch = "wasm32")]
+mod multicall;
+mod normalize;
+#[cfg(target_arch = "wasm32")]
+mod overview;
+mod pending;
+#[cfg(target_arch = "wasm32")]
+mod provider;
+#[cfg(target_arch = "wasm32")]
+mod pubsub;
+mod queue;
+mod recorder;
+mod response;
+mod signature;
+#[cfg(target_arch = "wasm32")]
 mod signer;
+#[cfg(target_arch = "wasm32")]
+mod simulate;
+#[cfg(target_arch = "wasm32")]
+mod state;
+#[cfg(target_arch = "wasm32")]
 mod transport;
+#[cfg(feature = "eip712")]
+mod typed_data;
+mod units;
+mod wallet;
+#[cfg(target_arch = "wasm32")]
+mod watch;
 
-pub use error::{Result, WindowError};
-pub use signer::WindowSigner;
+#[cfg(target_arch = "wasm32")]
+pub use builder::WindowProviderBuilderExt;
+#[cfg(target_arch = "wasm32")]
+pub use capabilities::{Capabilities, ChainCapabilities};
+#[cfg(target_arch = "wasm32")]
+pub use connection::ConnectionState;
+#[cfg(all(target_arch = "wasm32", feature = "dioxus"))]
+pub use dioxus::{use_provide_window_provider, use_window_provider};
+#[cfg(target_arch = "wasm32")]
+pub use discovery::{
+    discover_providers, duplicate_rdns, on_provider_announced, AnnouncementSubscription,
+    ProviderDetail, ProviderInfo,
+};
+#[cfg(target_arch = "wasm32")]
+pub use eip1271::ERC1271_MAGIC_VALUE;
+pub use ens::namehash;
+#[cfg(target_arch = "wasm32")]
+pub use erc20::TokenInfo;
+pub use error::{classify_error, Result, WindowError};
+#[cfg(target_arch = "wasm32")]
+pub use events::{ProviderMessage, Subscription};
+pub use fees::{Affordability, FeeEstimate};
+#[cfg(target_arch = "wasm32")]
+pub use multicall::MULTICALL3_ADDRESS;
+pub use normalize::AddressCase;
+#[cfg(target_arch = "wasm32")]
+pub use overview::AccountOverview;
+#[cfg(target_arch = "wasm32")]
+pub use provider::WindowProvider;
+#[cfg(target_arch = "wasm32")]
+pub use pubsub::{LogEvent, SubscribeParams, SubscriptionStream, WindowPubSub};
+pub use signature::{SignMethod, TypedDataV1Entry};
+#[cfg(target_arch = "wasm32")]
+pub use signer::{ConnectionInfo, WindowSigner};
+#[cfg(target_arch = "wasm32")]
+pub use simulate::SimulationResult;
+#[cfg(target_arch = "wasm32")]
+pub use state::ProviderState;
+#[cfg(target_arch = "wasm32")]
 pub use transport::WindowTransport;
+pub use units::{format_ether, format_gwei, from_hex_quantity, parse_gwei, to_hex_quantity};
+pub use wallet::{AddEthereumChainParameter, NativeCurrency, SwitchChainOutcome, WatchAssetParams};
+#[cfg(target_arch = "wasm32")]
+pub use watch::{PendingTransaction, TxFailure, TxStatus};
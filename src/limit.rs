@@ -0,0 +1,122 @@
+//! Cap on the number of wallet requests in flight at once
+
+use std::cell::RefCell;
+use std::future::poll_fn;
+use std::rc::Rc;
+use std::task::{Poll, Waker};
+
+/// Counting semaphore shared by all clones of a transport
+///
+/// Requests over the limit wait until a permit is released. Every waiter is
+/// woken on release and retries, so a waiter dropped before its turn can't
+/// stall the others.
+#[derive(Clone, Debug)]
+pub(crate) struct ConcurrencyLimit {
+    inner: Rc<RefCell<LimitInner>>,
+}
+
+#[derive(Debug)]
+struct LimitInner {
+    /// Maximum number of permits out at once
+    max: usize,
+    /// Permits currently out
+    in_flight: usize,
+    /// Wakers of requests waiting for a permit
+    waiters: Vec<Waker>,
+}
+
+/// Permission to have a request in flight, released when dropped
+pub(crate) struct Permit {
+    inner: Rc<RefCell<LimitInner>>,
+}
+
+impl ConcurrencyLimit {
+    /// Allow at most `max` requests in flight, at least one
+    pub(crate) fn new(max: usize) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(LimitInner {
+                max: max.max(1),
+                in_flight: 0,
+                waiters: Vec::new(),
+            })),
+        }
+    }
+
+    /// Wait until fewer than `max` requests are in flight
+    pub(crate) async fn acquire(&self) -> Permit {
+        poll_fn(|cx| {
+            let mut inner = self.inner.borrow_mut();
+            if inner.in_flight < inner.max {
+                inner.in_flight += 1;
+                Poll::Ready(())
+            } else {
+                inner.waiters.push(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await;
+
+        Permit {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut inner = self.inner.borrow_mut();
+            inner.in_flight -= 1;
+            std::mem::take(&mut inner.waiters)
+        };
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::task::noop_waker_ref;
+    use futures::FutureExt;
+    use std::task::Context;
+
+    #[test]
+    fn limits_permits_in_flight() {
+        let limit = ConcurrencyLimit::new(2);
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        let first = limit.acquire().now_or_never().unwrap();
+        let _second = limit.acquire().now_or_never().unwrap();
+        let mut third = Box::pin(limit.acquire());
+        assert!(third.poll_unpin(&mut cx).is_pending());
+
+        drop(first);
+        assert!(third.poll_unpin(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn allows_at_least_one() {
+        let limit = ConcurrencyLimit::new(0);
+        let permit = limit.acquire().now_or_never();
+        assert!(permit.is_some());
+        assert!(limit.acquire().now_or_never().is_none());
+    }
+
+    #[test]
+    fn dropped_waiters_dont_stall() {
+        let limit = ConcurrencyLimit::new(1);
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        let permit = limit.acquire().now_or_never().unwrap();
+        let mut dropped = Box::pin(limit.acquire());
+        let mut waiting = Box::pin(limit.acquire());
+        assert!(dropped.poll_unpin(&mut cx).is_pending());
+        assert!(waiting.poll_unpin(&mut cx).is_pending());
+
+        drop(dropped);
+        drop(permit);
+        assert!(waiting.poll_unpin(&mut cx).is_ready());
+    }
+}
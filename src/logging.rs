@@ -0,0 +1,115 @@
+//! Diagnostics for failed wallet requests
+
+use std::fmt;
+use std::rc::Rc;
+
+use serde_json::Value;
+
+use crate::error::WindowError;
+
+/// Correlation fields attached with `WindowTransport::with_context`
+///
+/// Cheap to clone: the fields are shared until one clone adds its own.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RequestContext {
+    fields: Rc<Vec<(String, String)>>,
+}
+
+impl RequestContext {
+    /// Set `key` to `value`, replacing an earlier value of the same key
+    pub(crate) fn with(mut self, key: String, value: String) -> Self {
+        let fields = Rc::make_mut(&mut self.fields);
+        match fields.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => fields.push((key, value)),
+        }
+        self
+    }
+}
+
+impl fmt::Display for RequestContext {
+    /// Space-separated `key=value` pairs, in insertion order
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, value)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Log a failed request with its normalized params at `warn` level
+///
+/// Successful requests aren't logged, so this stays quiet unless something
+/// goes wrong. With `redact` set, addresses in the params are masked.
+pub(crate) fn log_request_failure(
+    method: &str,
+    params: &Value,
+    error: &WindowError,
+    context: &RequestContext,
+    redact: bool,
+) {
+    let params = params.to_string();
+    let params = if redact {
+        redact_addresses(&params)
+    } else {
+        params
+    };
+
+    tracing::warn!(
+        method,
+        params = %params,
+        code = error.code(),
+        message = %error,
+        context = %context,
+        "wallet request failed"
+    );
+}
+
+/// Mask every 20-byte hex address, keeping the first and last 4 digits
+fn redact_addresses(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = String::with_capacity(s.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"0x") {
+            let digits = bytes[i + 2..]
+                .iter()
+                .take_while(|b| b.is_ascii_hexdigit())
+                .count();
+            // Longer hex strings (hashes, calldata) are left alone
+            if digits == 40 {
+                out.push_str(&s[i..i + 6]);
+                out.push('…');
+                out.push_str(&s[i + 38..i + 42]);
+            } else {
+                out.push_str(&s[i..i + 2 + digits]);
+            }
+            i += 2 + digits;
+        } else {
+            let len = s[i..].chars().next().map_or(1, char::len_utf8);
+            out.push_str(&s[i..i + len]);
+            i += len;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_addresses() {
+        assert_eq!(
+            redact_addresses(r#"["0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045","latest"]"#),
+            r#"["0xd8dA…6045","latest"]"#
+        );
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert_eq!(redact_addresses(&hash), hash);
+        assert_eq!(redact_addresses("0x 0x12 é"), "0x 0x12 é");
+    }
+}
@@ -0,0 +1,113 @@
+//! Scripted stand-in for window.ethereum in wasm tests
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::state::{ProviderState, SharedState};
+use crate::transport::WindowTransport;
+
+type Handler = Closure<dyn FnMut(String, JsValue) -> Result<JsValue, JsValue>>;
+
+#[wasm_bindgen(inline_js = r#"
+export function mock_ethereum(handler) {
+    const listeners = new Map();
+    return {
+        request({ method, params }) {
+            try {
+                return Promise.resolve(handler(method, params === undefined ? [] : params));
+            } catch (e) {
+                return Promise.reject(e);
+            }
+        },
+        on(event, listener) {
+            listeners.set(event, [...(listeners.get(event) || []), listener]);
+        },
+        removeListener(event, listener) {
+            listeners.set(event, (listeners.get(event) || []).filter((l) => l !== listener));
+        },
+        emit(event, payload) {
+            for (const listener of listeners.get(event) || []) {
+                listener(payload);
+            }
+        },
+    };
+}
+
+export function mock_emit(ethereum, event, payload) {
+    ethereum.emit(event, payload);
+}
+"#)]
+extern "C" {
+    fn mock_ethereum(handler: &Handler) -> JsValue;
+
+    fn mock_emit(ethereum: &JsValue, event: &str, payload: JsValue);
+}
+
+/// EIP-1193 provider answering from a handler and logging every request
+pub(crate) struct MockWallet {
+    ethereum: JsValue,
+    requests: Rc<RefCell<Vec<(String, Value)>>>,
+    _handler: Handler,
+}
+
+impl MockWallet {
+    /// Answer each request with `handler(method, params)`
+    ///
+    /// `Ok` resolves the request with the value, `Err` rejects it with the
+    /// value, typically a `{ code, message }` error object.
+    pub(crate) fn new(
+        mut handler: impl FnMut(&str, &Value) -> Result<Value, Value> + 'static,
+    ) -> Self {
+        let requests = Rc::new(RefCell::new(Vec::new()));
+        let log = requests.clone();
+        let handler = Handler::new(move |method: String, params: JsValue| {
+            let params = serde_wasm_bindgen::from_value(params).unwrap_or(Value::Null);
+            let result = handler(&method, &params);
+            log.borrow_mut().push((method, params));
+            result.map(|value| to_js(&value)).map_err(|e| to_js(&e))
+        });
+
+        Self {
+            ethereum: mock_ethereum(&handler),
+            requests,
+            _handler: handler,
+        }
+    }
+
+    /// The provider object, to pass where window.ethereum would go
+    pub(crate) fn ethereum(&self) -> JsValue {
+        self.ethereum.clone()
+    }
+
+    /// A transport talking to this wallet
+    pub(crate) fn transport(&self) -> WindowTransport {
+        let state = SharedState::new(&self.ethereum, ProviderState::default());
+        WindowTransport::with_state(self.ethereum(), state)
+    }
+
+    /// Emit a provider event to the listeners registered with `on`
+    pub(crate) fn emit(&self, event: &str, payload: Value) {
+        mock_emit(&self.ethereum, event, to_js(&payload));
+    }
+
+    /// Requests received so far, oldest first
+    pub(crate) fn requests(&self) -> Vec<(String, Value)> {
+        self.requests.borrow().clone()
+    }
+}
+
+/// EIP-1193 error object to reject a request with
+pub(crate) fn rpc_error(code: i64, message: &str) -> Value {
+    json!({ "code": code, "message": message })
+}
+
+/// Convert to a plain JS value, objects included
+fn to_js(value: &Value) -> JsValue {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .unwrap_or(JsValue::NULL)
+}
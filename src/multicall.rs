@@ -0,0 +1,68 @@
+//! Batched view calls through an on-chain Multicall3 contract
+
+use alloy_primitives::{address, Address, Bytes};
+use alloy_sol_types::{sol, SolCall};
+
+use crate::error::Result;
+use crate::provider::WindowProvider;
+
+/// Canonical Multicall3 address, deployed at the same address on most chains
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+sol! {
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Result[] memory returnData);
+    }
+}
+
+impl WindowProvider {
+    /// Execute several view calls in a single `eth_call` through Multicall3
+    ///
+    /// Each call is a `(target, calldata)` pair; the raw return data is
+    /// returned in the same order. Turns N wallet round-trips into one. If any
+    /// call reverts, the whole multicall reverts.
+    pub async fn multicall(&self, calls: Vec<(Address, Bytes)>) -> Result<Vec<Bytes>> {
+        self.multicall_at(MULTICALL3_ADDRESS, calls).await
+    }
+
+    /// Like [`multicall`](Self::multicall), for chains where Multicall3 is
+    /// deployed at a non-canonical address
+    pub async fn multicall_at(
+        &self,
+        multicall: Address,
+        calls: Vec<(Address, Bytes)>,
+    ) -> Result<Vec<Bytes>> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let calls = calls
+            .into_iter()
+            .map(|(target, call_data)| IMulticall3::Call3 {
+                target,
+                allowFailure: false,
+                callData: call_data,
+            })
+            .collect();
+        let output = self
+            .eth_call(
+                multicall,
+                IMulticall3::aggregate3Call { calls }.abi_encode().into(),
+            )
+            .await?;
+
+        let results = IMulticall3::aggregate3Call::abi_decode_returns(&output)?;
+        Ok(results.into_iter().map(|r| r.returnData).collect())
+    }
+}
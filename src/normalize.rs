@@ -0,0 +1,627 @@
+//! Param and response normalization - rewrites params into the shape wallets
+//! expect and results into the shape alloy expects
+
+use alloy_primitives::{Address, TxHash, B256, U256};
+use alloy_rpc_types_eth::BlockId;
+use serde_json::{json, Number, Value};
+use wasm_bindgen::JsValue;
+
+use crate::error::{classify_error, Result, WindowError};
+use crate::units::{from_hex_quantity, to_hex_quantity};
+
+/// JSON-RPC version wallets must report when they return a full envelope
+const JSONRPC_VERSION: &str = "2.0";
+
+/// Transaction fields that are coerced from JSON numbers to `0x`-prefixed hex
+/// quantities for `eth_sendTransaction` and `eth_estimateGas`
+const QUANTITY_FIELDS: &[&str] = &[
+    "gas",
+    "gasPrice",
+    "maxFeePerGas",
+    "maxPriorityFeePerGas",
+    "value",
+    "nonce",
+];
+
+/// Methods whose result is a single hex quantity
+const QUANTITY_RESULT_METHODS: &[&str] = &[
+    "eth_blockNumber",
+    "eth_chainId",
+    "eth_gasPrice",
+    "eth_maxPriorityFeePerGas",
+    "eth_blobBaseFee",
+    "eth_getBalance",
+    "eth_getTransactionCount",
+    "eth_estimateGas",
+];
+
+/// Read methods taking a block parameter, with the index of that parameter
+const BLOCK_PARAM_METHODS: &[(&str, usize)] = &[
+    ("eth_getBalance", 1),
+    ("eth_getCode", 1),
+    ("eth_getTransactionCount", 1),
+    ("eth_getStorageAt", 2),
+    ("eth_call", 1),
+    ("eth_estimateGas", 1),
+    ("eth_createAccessList", 1),
+    ("eth_getProof", 2),
+];
+
+/// Casing applied to addresses in outgoing params by
+/// [`WindowTransport::with_address_case`](crate::WindowTransport::with_address_case)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressCase {
+    /// EIP-55 mixed-case checksum
+    Checksum,
+    /// All lowercase
+    Lowercase,
+}
+
+/// Parse a chain ID reported by the wallet
+///
+/// EIP-1193 requires a `0x`-prefixed hex string, but a few wallets report a
+/// number or a decimal string instead.
+pub(crate) fn parse_chain_id(value: &Value) -> Result<u64> {
+    let chain_id = match value {
+        Value::String(s) => match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        Value::Number(n) => integral_number(n),
+        _ => None,
+    };
+    chain_id.ok_or_else(|| WindowError::Rpc(format!("Invalid chain ID: {}", value)))
+}
+
+/// Parse a chain ID straight from a wallet response or event payload
+pub(crate) fn chain_id_from_js(value: JsValue) -> Result<u64> {
+    parse_chain_id(&serde_wasm_bindgen::from_value(value)?)
+}
+
+/// Fail if `value` nests deeper than `max_depth` levels
+///
+/// Walks with an explicit stack, so it's safe to run on untrusted params
+/// before anything recurses into them.
+pub(crate) fn check_depth(value: &Value, max_depth: usize) -> Result<()> {
+    let mut stack = vec![(value, 0)];
+    while let Some((value, depth)) = stack.pop() {
+        if depth > max_depth {
+            return Err(WindowError::DepthLimitExceeded(max_depth));
+        }
+        match value {
+            Value::Array(items) => stack.extend(items.iter().map(|item| (item, depth + 1))),
+            Value::Object(obj) => stack.extend(obj.values().map(|item| (item, depth + 1))),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Normalize the params of a request before they are sent to the wallet
+pub(crate) fn normalize_params(method: &str, params: Value) -> Value {
+    match method {
+        // window.ethereum expects "data" rather than "input"
+        "eth_call" => rewrite_input_to_data(params),
+        // Wallets require hex quantities, alloy may serialize plain numbers
+        "eth_sendTransaction" | "eth_estimateGas" => {
+            normalize_access_list(coerce_quantities(params))
+        }
+        _ => params,
+    }
+}
+
+/// Transform "input" to "data" in the transaction object of `eth_call`
+fn rewrite_input_to_data(mut params: Value) -> Value {
+    if let Some(Value::Object(tx)) = params.get_mut(0) {
+        if let Some(input) = tx.remove("input") {
+            tx.insert("data".to_string(), input);
+        }
+    }
+    params
+}
+
+/// Convert quantity fields of the transaction object to minimal hex quantities
+fn coerce_quantities(mut params: Value) -> Value {
+    if let Some(Value::Object(tx)) = params.get_mut(0) {
+        for field in QUANTITY_FIELDS {
+            let Some(value) = tx.get_mut(*field) else {
+                continue;
+            };
+            let quantity = match value {
+                Value::Number(n) => n.as_u64().map(U256::from),
+                // Re-encode to strip leading zeros some callers leave in
+                Value::String(s) => from_hex_quantity(s).ok(),
+                _ => None,
+            };
+            if let Some(quantity) = quantity {
+                *value = Value::String(to_hex_quantity(quantity));
+            }
+        }
+    }
+    params
+}
+
+/// Set `chainId` on the transaction object, the first param
+pub(crate) fn insert_chain_id(mut params: Value, chain_id: u64) -> Value {
+    if let Some(Value::Object(tx)) = params.get_mut(0) {
+        tx.insert(
+            "chainId".to_string(),
+            Value::String(to_hex_quantity(U256::from(chain_id))),
+        );
+    }
+    params
+}
+
+/// Whether the transaction object, the first param, has no sender
+///
+/// A `null` sender counts as missing.
+pub(crate) fn lacks_from(params: &Value) -> bool {
+    params
+        .get(0)
+        .and_then(Value::as_object)
+        .is_some_and(|tx| tx.get("from").is_none_or(Value::is_null))
+}
+
+/// Set `from` on the transaction object if it [lacks one](lacks_from)
+///
+/// An explicit sender is never replaced.
+pub(crate) fn insert_from(mut params: Value, from: Address) -> Value {
+    if !lacks_from(&params) {
+        return params;
+    }
+    if let Some(Value::Object(tx)) = params.get_mut(0) {
+        tx.insert("from".to_string(), json!(from));
+    }
+    params
+}
+
+/// Complete the EIP-2930 access list of the transaction object, if any
+///
+/// Wallets validate `accessList` strictly: every entry needs a `storageKeys`
+/// array (possibly empty) and every key must be a full 32-byte word. Alloy
+/// always produces that shape, but hand-built JSON often omits empty key
+/// lists or writes keys as short quantities (`0x1`), so both are fixed up.
+fn normalize_access_list(mut params: Value) -> Value {
+    let Some(entries) = params
+        .get_mut(0)
+        .and_then(|tx| tx.get_mut("accessList"))
+        .and_then(Value::as_array_mut)
+    else {
+        return params;
+    };
+
+    for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+        let keys = entry
+            .entry("storageKeys")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        for key in keys.as_array_mut().into_iter().flatten() {
+            let Value::String(hex) = key else {
+                continue;
+            };
+            if let Ok(slot) = from_hex_quantity(hex) {
+                *key = json!(B256::from(slot));
+            }
+        }
+    }
+    params
+}
+
+/// Unwrap a wallet result that carries a full JSON-RPC envelope
+///
+/// EIP-1193 `request` resolves to the bare result, but some wallets resolve to
+/// the raw `{ jsonrpc, id, result | error }` response instead. Enveloped
+/// results must declare `jsonrpc: "2.0"` or are rejected as
+/// [`InvalidResponse`](WindowError::InvalidResponse); their `error` member is
+/// classified like a rejected request. Anything else is returned unchanged.
+pub(crate) fn unwrap_envelope(result: Value) -> Result<Value> {
+    let Value::Object(mut obj) = result else {
+        return Ok(result);
+    };
+    if !obj.contains_key("jsonrpc") || !(obj.contains_key("result") || obj.contains_key("error")) {
+        return Ok(Value::Object(obj));
+    }
+
+    if obj["jsonrpc"] != JSONRPC_VERSION {
+        return Err(WindowError::InvalidResponse(format!(
+            "unsupported JSON-RPC version in wallet response: {}",
+            obj["jsonrpc"]
+        )));
+    }
+
+    match obj.remove("error") {
+        Some(error) if !error.is_null() => Err(error_from_object(&error)),
+        _ => Ok(obj.remove("result").unwrap_or(Value::Null)),
+    }
+}
+
+/// Convert quantity results that a wallet returned as JSON numbers
+///
+/// Some wallets resolve `eth_blockNumber`, `eth_chainId` and similar with a
+/// JS number instead of a hex string, which alloy can't parse. Those become
+/// hex quantities; `net_version`, a decimal string by spec, becomes a
+/// decimal string.
+pub(crate) fn normalize_result(method: &str, result: Value) -> Value {
+    let Value::Number(n) = &result else {
+        return result;
+    };
+    let Some(n) = integral_number(n) else {
+        return result;
+    };
+
+    if method == "net_version" {
+        Value::String(n.to_string())
+    } else if QUANTITY_RESULT_METHODS.contains(&method) {
+        Value::String(to_hex_quantity(U256::from(n)))
+    } else {
+        result
+    }
+}
+
+/// Value of a non-negative integral JSON number, including ones that came
+/// from JS as doubles (`1.0`)
+fn integral_number(n: &Number) -> Option<u64> {
+    // JS numbers are doubles; integral ones above u64 can't be exact anyway
+    n.as_u64().or_else(|| {
+        n.as_f64()
+            .filter(|f| f.fract() == 0.0 && *f >= 0.0)
+            .map(|f| f as u64)
+    })
+}
+
+/// Check a transaction submission resolved to a transaction hash
+///
+/// Some wallets resolve `eth_sendTransaction` with an error object instead of
+/// rejecting. A `{ error }` or `{ code, message }` result is classified like a
+/// rejected request, and a `null`/`undefined` result (seen when the approval
+/// state is ambiguous) is [`WindowError::NoTransactionHash`]. Any other value
+/// that isn't a 32-byte hash is an error rather than something alloy would
+/// misreport as a bad hash.
+pub(crate) fn check_transaction_hash(method: &str, result: Value) -> Result<Value> {
+    if !matches!(method, "eth_sendTransaction" | "eth_sendRawTransaction") {
+        return Ok(result);
+    }
+
+    match &result {
+        Value::String(hash) if hash.len() == 66 && hash.parse::<TxHash>().is_ok() => Ok(result),
+        Value::Null => Err(WindowError::NoTransactionHash),
+        Value::Object(obj) => match obj.get("error") {
+            Some(error) if error.is_object() => Err(error_from_object(error)),
+            _ if obj.contains_key("code") || obj.contains_key("message") => {
+                Err(error_from_object(&result))
+            }
+            _ => Err(WindowError::Rpc(format!(
+                "{} returned an object instead of a transaction hash: {}",
+                method, result
+            ))),
+        },
+        _ => Err(WindowError::Rpc(format!(
+            "{} returned an invalid transaction hash: {}",
+            method, result
+        ))),
+    }
+}
+
+/// Classify a JSON-RPC `{ code, message, data }` error object
+fn error_from_object(error: &Value) -> WindowError {
+    classify_error(
+        error["code"].as_i64(),
+        error["message"].as_str().map(str::to_string),
+        match &error["data"] {
+            Value::Null => None,
+            Value::String(data) => Some(data.clone()),
+            data => Some(data.to_string()),
+        },
+    )
+}
+
+/// Fill in the block parameter of read methods when the caller omitted it
+///
+/// Only an absent or `null` block parameter is replaced; explicit tags,
+/// numbers and hashes are left alone.
+pub(crate) fn apply_default_block(method: &str, params: Value, block: BlockId) -> Value {
+    let Some(&(_, index)) = BLOCK_PARAM_METHODS.iter().find(|(m, _)| *m == method) else {
+        return params;
+    };
+
+    match params {
+        Value::Array(mut arr) if arr.len() == index => {
+            arr.push(json!(block));
+            Value::Array(arr)
+        }
+        Value::Array(mut arr) if arr.get(index).is_some_and(Value::is_null) => {
+            arr[index] = json!(block);
+            Value::Array(arr)
+        }
+        params => params,
+    }
+}
+
+/// Rewrite every address-shaped string in `params` to the given casing
+///
+/// Any `0x`-prefixed string of exactly 40 hex digits is treated as an address.
+pub(crate) fn normalize_address_case(params: Value, case: AddressCase) -> Value {
+    match params {
+        Value::String(s) => match s.parse::<Address>() {
+            Ok(address) if s.len() == 42 && s.starts_with("0x") => Value::String(match case {
+                AddressCase::Checksum => address.to_checksum(None),
+                AddressCase::Lowercase => s.to_lowercase(),
+            }),
+            _ => Value::String(s),
+        },
+        Value::Array(arr) => Value::Array(
+            arr.into_iter()
+                .map(|v| normalize_address_case(v, case))
+                .collect(),
+        ),
+        Value::Object(obj) => Value::Object(
+            obj.into_iter()
+                .map(|(k, v)| (k, normalize_address_case(v, case)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rpc_types_eth::BlockNumberOrTag;
+
+    const ADDRESS: &str = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+
+    #[test]
+    fn rewrites_input_to_data() {
+        let params = normalize_params(
+            "eth_call",
+            json!([{ "to": ADDRESS, "input": "0x1234" }, "latest"]),
+        );
+        assert_eq!(
+            params,
+            json!([{ "to": ADDRESS, "data": "0x1234" }, "latest"])
+        );
+        // Other methods keep their params untouched
+        let params = json!([{ "input": "0x1234" }]);
+        assert_eq!(normalize_params("eth_getLogs", params.clone()), params);
+    }
+
+    #[test]
+    fn coerces_quantities() {
+        let params = normalize_params(
+            "eth_sendTransaction",
+            json!([{
+                "from": ADDRESS,
+                "gas": 21000,
+                "value": "0x0001",
+                "nonce": 0,
+                "maxFeePerGas": "not a quantity",
+                "data": "0x00",
+            }]),
+        );
+        assert_eq!(
+            params,
+            json!([{
+                "from": ADDRESS,
+                "gas": "0x5208",
+                "value": "0x1",
+                "nonce": "0x0",
+                "maxFeePerGas": "not a quantity",
+                "data": "0x00",
+            }])
+        );
+    }
+
+    #[test]
+    fn unwraps_envelopes() {
+        assert_eq!(
+            unwrap_envelope(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x1" })).unwrap(),
+            json!("0x1")
+        );
+        let error = unwrap_envelope(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": 4001, "message": "User rejected" },
+        }));
+        assert!(matches!(error, Err(WindowError::UserRejected)));
+        let error = unwrap_envelope(json!({ "jsonrpc": "1.0", "result": "0x1" })).unwrap_err();
+        assert!(matches!(error, WindowError::InvalidResponse(_)));
+        assert_eq!(error.code(), Some(-32603));
+        // Bare results, including objects that merely have a `result` field
+        let block = json!({ "number": "0x1", "result": "ok" });
+        assert_eq!(unwrap_envelope(block.clone()).unwrap(), block);
+        assert_eq!(unwrap_envelope(json!("0x1")).unwrap(), json!("0x1"));
+    }
+
+    #[test]
+    fn normalizes_address_case() {
+        let lower = ADDRESS.to_lowercase();
+        let params = json!([{ "from": lower, "to": [ADDRESS], "data": "0x1234" }]);
+        assert_eq!(
+            normalize_address_case(params.clone(), AddressCase::Checksum),
+            json!([{ "from": ADDRESS, "to": [ADDRESS], "data": "0x1234" }])
+        );
+        assert_eq!(
+            normalize_address_case(params, AddressCase::Lowercase),
+            json!([{ "from": lower, "to": [lower], "data": "0x1234" }])
+        );
+        // Not address-shaped: hashes and unprefixed hex
+        let hash = format!("0x{}", "ab".repeat(32));
+        let unprefixed = &lower[2..];
+        assert_eq!(
+            normalize_address_case(json!([hash, unprefixed]), AddressCase::Checksum),
+            json!([hash, unprefixed])
+        );
+    }
+
+    #[test]
+    fn checks_transaction_hashes() {
+        let hash = json!(format!("0x{}", "ab".repeat(32)));
+        assert_eq!(
+            check_transaction_hash("eth_sendTransaction", hash.clone()).unwrap(),
+            hash
+        );
+        assert!(matches!(
+            check_transaction_hash("eth_sendTransaction", Value::Null),
+            Err(WindowError::NoTransactionHash)
+        ));
+        assert!(matches!(
+            check_transaction_hash(
+                "eth_sendTransaction",
+                json!({ "error": { "code": 4001, "message": "denied" } })
+            ),
+            Err(WindowError::UserRejected)
+        ));
+        assert!(matches!(
+            check_transaction_hash(
+                "eth_sendRawTransaction",
+                json!({ "code": -32000, "message": "nonce too low" })
+            ),
+            Err(WindowError::Provider { code: -32000, .. })
+        ));
+        assert!(matches!(
+            check_transaction_hash("eth_sendTransaction", json!("0x1234")),
+            Err(WindowError::Rpc(_))
+        ));
+        assert!(check_transaction_hash("eth_call", Value::Null).is_ok());
+    }
+
+    #[test]
+    fn applies_default_block() {
+        let block = BlockId::Number(BlockNumberOrTag::Safe);
+        assert_eq!(
+            apply_default_block("eth_getBalance", json!([ADDRESS]), block),
+            json!([ADDRESS, "safe"])
+        );
+        assert_eq!(
+            apply_default_block("eth_getStorageAt", json!([ADDRESS, "0x0", null]), block),
+            json!([ADDRESS, "0x0", "safe"])
+        );
+        assert_eq!(
+            apply_default_block("eth_getBalance", json!([ADDRESS, "latest"]), block),
+            json!([ADDRESS, "latest"])
+        );
+        assert_eq!(
+            apply_default_block("eth_getLogs", json!([{}]), block),
+            json!([{}])
+        );
+    }
+
+    #[test]
+    fn normalizes_access_lists() {
+        let params = normalize_params(
+            "eth_estimateGas",
+            json!([{
+                "accessList": [
+                    { "address": ADDRESS },
+                    { "address": ADDRESS, "storageKeys": ["0x1"] },
+                ],
+            }]),
+        );
+        assert_eq!(
+            params[0]["accessList"],
+            json!([
+                { "address": ADDRESS, "storageKeys": [] },
+                { "address": ADDRESS, "storageKeys": [B256::with_last_byte(1)] },
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_chain_ids() {
+        assert_eq!(parse_chain_id(&json!("0x89")).unwrap(), 137);
+        assert_eq!(parse_chain_id(&json!("0X1")).unwrap(), 1);
+        assert_eq!(parse_chain_id(&json!("137")).unwrap(), 137);
+        assert_eq!(parse_chain_id(&json!(10)).unwrap(), 10);
+        assert_eq!(parse_chain_id(&json!(10.0)).unwrap(), 10);
+        assert!(parse_chain_id(&json!("0x")).is_err());
+        assert!(parse_chain_id(&json!("mainnet")).is_err());
+        assert!(parse_chain_id(&json!(-1)).is_err());
+        assert!(parse_chain_id(&Value::Null).is_err());
+    }
+
+    #[test]
+    fn normalizes_numeric_results() {
+        assert_eq!(
+            normalize_result("eth_blockNumber", json!(255)),
+            json!("0xff")
+        );
+        assert_eq!(normalize_result("eth_chainId", json!(1.0)), json!("0x1"));
+        assert_eq!(normalize_result("net_version", json!(1)), json!("1"));
+        assert_eq!(
+            normalize_result("eth_blockNumber", json!("0xff")),
+            json!("0xff")
+        );
+        assert_eq!(normalize_result("eth_call", json!(1)), json!(1));
+        assert_eq!(normalize_result("eth_gasPrice", json!(1.5)), json!(1.5));
+    }
+
+    #[test]
+    fn checks_depth() {
+        let mut value = json!(1);
+        for _ in 0..10 {
+            value = json!([value]);
+        }
+        assert!(check_depth(&value, 10).is_ok());
+        assert!(matches!(
+            check_depth(&value, 9),
+            Err(WindowError::DepthLimitExceeded(9))
+        ));
+        assert!(check_depth(&json!({ "a": [] }), 1).is_ok());
+        assert!(check_depth(&json!({ "a": [1] }), 1).is_err());
+    }
+
+    #[test]
+    fn checks_depth_without_recursing() {
+        let mut value = json!(null);
+        for _ in 0..100_000 {
+            value = Value::Array(vec![value]);
+        }
+        assert!(check_depth(&value, 128).is_err());
+        // Dropping nested values recurses, so unwind them by hand
+        while let Value::Array(mut items) = value {
+            value = items.pop().unwrap_or(Value::Null);
+        }
+    }
+
+    #[test]
+    fn inserts_chain_id() {
+        assert_eq!(
+            insert_chain_id(json!([{ "from": ADDRESS }]), 137),
+            json!([{ "from": ADDRESS, "chainId": "0x89" }])
+        );
+        assert_eq!(insert_chain_id(json!([]), 137), json!([]));
+    }
+
+    #[test]
+    fn inserts_missing_senders() {
+        let from: Address = ADDRESS.parse().unwrap();
+        for tx in [json!({ "to": ADDRESS }), json!({ "from": null })] {
+            let params = json!([tx]);
+            assert!(lacks_from(&params));
+            assert_eq!(insert_from(params, from)[0]["from"], json!(from));
+        }
+    }
+
+    #[test]
+    fn keeps_explicit_senders() {
+        let from = Address::repeat_byte(0xaa);
+        let params = json!([{ "from": ADDRESS, "to": ADDRESS }]);
+        assert!(!lacks_from(&params));
+        assert_eq!(insert_from(params.clone(), from), params);
+        // Params without a transaction object are left alone
+        assert_eq!(insert_from(json!([]), from), json!([]));
+        assert_eq!(insert_from(json!(["0x1"]), from), json!(["0x1"]));
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn parses_chain_ids_from_js() {
+        assert_eq!(chain_id_from_js(JsValue::from_str("0x89")).unwrap(), 137);
+        assert_eq!(chain_id_from_js(JsValue::from_f64(137.0)).unwrap(), 137);
+        assert!(chain_id_from_js(JsValue::NULL).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! Account summary for wallet dashboards
+
+use alloy_primitives::{Address, U256, U64};
+use serde_json::{json, Value};
+
+use crate::accounts::parse_accounts;
+use crate::error::{Result, WindowError};
+use crate::provider::WindowProvider;
+
+/// Active account of the wallet with its chain and native balance
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountOverview {
+    /// Active account
+    pub address: Address,
+    /// Chain ID the wallet is connected to
+    pub chain_id: u64,
+    /// Native balance of the account in wei
+    pub balance: U256,
+}
+
+impl WindowProvider {
+    /// Get the active account, chain ID and balance in a single round-trip
+    ///
+    /// `eth_accounts`, `eth_chainId` and `eth_getBalance` are issued together
+    /// (the equivalent of `Promise.all`). The balance request needs the
+    /// account up front, so the last known account of the transport's state
+    /// is used; if there is none yet, or the wallet has switched accounts in
+    /// the meantime, the balance is fetched again in a second round-trip.
+    /// Fails with [`WindowError::NoAccounts`] if no account is exposed.
+    pub async fn account_overview(&self) -> Result<AccountOverview> {
+        let known = self.transport().state().address;
+
+        let mut requests = vec![
+            ("eth_accounts".to_string(), json!([])),
+            ("eth_chainId".to_string(), json!([])),
+        ];
+        if let Some(address) = known {
+            requests.push(("eth_getBalance".to_string(), json!([address, "latest"])));
+        }
+        let mut results = self.transport().request_all(requests).await.into_iter();
+        let mut next = || results.next().unwrap_or(Ok(Value::Null));
+
+        let accounts = parse_accounts(&next()?)?;
+        let address = *accounts.first().ok_or(WindowError::NoAccounts)?;
+        let chain_id: U64 = serde_json::from_value(next()?)?;
+
+        let balance = match next() {
+            balance if known == Some(address) => serde_json::from_value(balance?)?,
+            _ => {
+                self.request("eth_getBalance", json!([address, "latest"]))
+                    .await?
+            }
+        };
+
+        Ok(AccountOverview {
+            address,
+            chain_id: chain_id.to(),
+            balance,
+        })
+    }
+}
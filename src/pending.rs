@@ -0,0 +1,85 @@
+//! Count of in-flight requests for loading indicators
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::Rc;
+
+/// Callback notified with the new count whenever it changes
+type ChangeCallback = Box<dyn FnMut(usize)>;
+
+/// In-flight request counter shared by all clones of a transport
+#[derive(Clone, Default)]
+pub(crate) struct PendingRequests {
+    count: Rc<Cell<usize>>,
+    on_change: Rc<RefCell<Option<ChangeCallback>>>,
+}
+
+impl PendingRequests {
+    /// Number of requests that haven't completed yet
+    pub(crate) fn get(&self) -> usize {
+        self.count.get()
+    }
+
+    /// Replace the change callback
+    pub(crate) fn set_callback(&self, callback: impl FnMut(usize) + 'static) {
+        *self.on_change.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Count a request as pending until the returned guard is dropped
+    pub(crate) fn start(&self) -> PendingGuard {
+        self.set(self.count.get() + 1);
+        PendingGuard {
+            pending: self.clone(),
+        }
+    }
+
+    fn set(&self, count: usize) {
+        self.count.set(count);
+        // A callback that issues requests itself isn't re-entered
+        if let Ok(mut on_change) = self.on_change.try_borrow_mut() {
+            if let Some(callback) = on_change.as_mut() {
+                callback(count);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for PendingRequests {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingRequests")
+            .field("count", &self.count.get())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Pending request, no longer counted once dropped (completed, failed or cancelled)
+pub(crate) struct PendingGuard {
+    pending: PendingRequests,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.pending.set(self.pending.count.get().saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_requests_until_guards_drop() {
+        let pending = PendingRequests::default();
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let recorded = changes.clone();
+        pending.set_callback(move |count| recorded.borrow_mut().push(count));
+
+        let first = pending.start();
+        let second = pending.clone().start();
+        assert_eq!(pending.get(), 2);
+        drop(first);
+        drop(second);
+        assert_eq!(pending.get(), 0);
+        assert_eq!(*changes.borrow(), vec![1, 2, 1, 0]);
+    }
+}
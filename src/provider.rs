@@ -0,0 +1,417 @@
+//! WindowProvider implementation - typed helpers for common wallet-routed reads
+
+use std::future::Future;
+use std::time::Duration;
+
+use alloy_primitives::{Address, Bytes, TxHash, B256, U256, U64};
+use alloy_rpc_types_eth::state::StateOverride;
+use alloy_rpc_types_eth::{
+    AccessListResult, Block, BlockId, Transaction, TransactionReceipt, TransactionRequest,
+};
+use alloy_sol_types::SolCall;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::accounts::parse_accounts;
+use crate::erc20::DecimalsCache;
+use crate::error::{Result, WindowError};
+use crate::js_bindings::sleep;
+use crate::normalize::parse_chain_id;
+use crate::transport::WindowTransport;
+use crate::units::to_hex_quantity;
+
+/// Interval between receipt polls - long enough not to flood the wallet
+pub(crate) const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Thin typed wrapper around [`WindowTransport`] for requests that don't need
+/// a full Alloy provider stack
+#[derive(Clone, Debug)]
+pub struct WindowProvider {
+    transport: WindowTransport,
+    /// Token decimals fetched on the current chain
+    decimals: DecimalsCache,
+}
+
+impl WindowProvider {
+    /// Create a new WindowProvider from window.ethereum
+    pub fn new() -> Result<Self> {
+        Ok(Self::from_transport(WindowTransport::new()?))
+    }
+
+    /// Create a WindowProvider on top of an existing transport
+    pub fn from_transport(transport: WindowTransport) -> Self {
+        let decimals = DecimalsCache::new(&transport);
+        Self {
+            transport,
+            decimals,
+        }
+    }
+
+    /// Get the underlying transport
+    pub fn transport(&self) -> &WindowTransport {
+        &self.transport
+    }
+
+    /// Get the token decimals cache shared by clones of this provider
+    pub(crate) fn decimals_cache(&self) -> &DecimalsCache {
+        &self.decimals
+    }
+
+    /// Get the chain ID the wallet is connected to
+    pub async fn chain_id(&self) -> Result<u64> {
+        let chain_id: Value = self.request("eth_chainId", json!([])).await?;
+        parse_chain_id(&chain_id)
+    }
+
+    /// Get the latest block number
+    pub async fn block_number(&self) -> Result<u64> {
+        let number: U64 = self.request("eth_blockNumber", json!([])).await?;
+        Ok(number.to())
+    }
+
+    /// Make sure the wallet is on `chain_id`, asking it to switch if not
+    ///
+    /// Sends `wallet_switchEthereumChain` when the wallet is on another chain.
+    /// If the user declines the switch this returns
+    /// [`WindowError::ChainMismatch`]; other failures (e.g. code 4902 for a
+    /// chain the wallet doesn't know) are returned as-is.
+    pub async fn ensure_chain(&self, chain_id: u64) -> Result<()> {
+        let current = self.chain_id().await?;
+        if current == chain_id {
+            return Ok(());
+        }
+
+        match self.switch_chain(chain_id).await {
+            Ok(_) => Ok(()),
+            Err(WindowError::UserRejected) => Err(WindowError::ChainMismatch {
+                expected: chain_id,
+                actual: current,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetch the latest block
+    pub async fn latest_block(&self) -> Result<Block> {
+        self.request::<Option<Block>>("eth_getBlockByNumber", json!(["latest", false]))
+            .await?
+            .ok_or_else(|| WindowError::Rpc("latest block not found".to_string()))
+    }
+
+    /// Get a transaction by hash
+    ///
+    /// Returns `None` while the transaction is unknown to the wallet's node,
+    /// so "pending transaction" UIs can poll until it appears.
+    pub async fn get_transaction(&self, hash: TxHash) -> Result<Option<Transaction>> {
+        self.request("eth_getTransactionByHash", json!([hash]))
+            .await
+    }
+
+    /// Get a transaction receipt by hash, `None` until the transaction is mined
+    pub async fn get_transaction_receipt(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<TransactionReceipt>> {
+        self.request("eth_getTransactionReceipt", json!([hash]))
+            .await
+    }
+
+    /// Send a transaction after checking its sender is the active account
+    ///
+    /// If the user switched accounts since the dapp built `tx`, the wallet
+    /// would sign with the newly active account or fail. This compares `from`
+    /// against `eth_accounts[0]` first and returns
+    /// [`WindowError::AccountMismatch`] so the dapp can refresh its state.
+    /// Transactions without `from` are sent as-is.
+    pub async fn send_transaction_checked(&self, tx: TransactionRequest) -> Result<TxHash> {
+        if let Some(expected) = tx.from {
+            let accounts = parse_accounts(&self.request("eth_accounts", json!([])).await?)?;
+            let actual = *accounts.first().ok_or(WindowError::NoAccounts)?;
+            if actual != expected {
+                return Err(WindowError::AccountMismatch { expected, actual });
+            }
+        }
+
+        self.request("eth_sendTransaction", json!([tx])).await
+    }
+
+    /// Send a transaction and return its hash together with a receipt watcher
+    ///
+    /// The hash is available as soon as `eth_sendTransaction` resolves, so UIs
+    /// can display it immediately. The returned future polls
+    /// `eth_getTransactionReceipt` until the transaction is mined.
+    pub async fn send_and_watch(
+        &self,
+        tx: TransactionRequest,
+    ) -> Result<(TxHash, impl Future<Output = Result<TransactionReceipt>>)> {
+        let hash: TxHash = self.request("eth_sendTransaction", json!([tx])).await?;
+
+        let provider = self.clone();
+        let receipt = async move {
+            loop {
+                if let Some(receipt) = provider.get_transaction_receipt(hash).await? {
+                    return Ok(receipt);
+                }
+                sleep(RECEIPT_POLL_INTERVAL).await;
+            }
+        };
+
+        Ok((hash, receipt))
+    }
+
+    /// Execute `eth_call` against a specific block, optionally with state overrides
+    ///
+    /// Many injected providers don't forward state overrides to their node.
+    /// When overrides are supplied and the wallet rejects the call as an
+    /// unknown method or invalid params, this returns
+    /// [`WindowError::UnsupportedMethod`] so callers can fall back.
+    pub async fn call_at(
+        &self,
+        tx: &TransactionRequest,
+        block: BlockId,
+        overrides: Option<StateOverride>,
+    ) -> Result<Bytes> {
+        let params = match &overrides {
+            Some(overrides) => json!([tx, block, overrides]),
+            None => json!([tx, block]),
+        };
+
+        match self.request("eth_call", params).await {
+            Err(WindowError::MethodNotFound(message))
+            | Err(WindowError::InvalidParams(message))
+            | Err(WindowError::UnsupportedMethod(message))
+                if overrides.is_some() =>
+            {
+                Err(WindowError::UnsupportedMethod(format!(
+                    "eth_call state overrides: {}",
+                    message
+                )))
+            }
+            result => result,
+        }
+    }
+
+    /// Read a raw storage slot of a contract at a block (`eth_getStorageAt`)
+    ///
+    /// The slot is sent as a hex quantity. Packed variables have to be
+    /// extracted from the returned 32-byte word by the caller.
+    pub async fn get_storage_at(
+        &self,
+        address: Address,
+        slot: U256,
+        block: BlockId,
+    ) -> Result<B256> {
+        self.request(
+            "eth_getStorageAt",
+            json!([address, to_hex_quantity(slot), block]),
+        )
+        .await
+    }
+
+    /// Get the deployed bytecode of an address at a block (`eth_getCode`)
+    ///
+    /// Returns empty bytes for accounts without code.
+    pub async fn get_code_at(&self, address: Address, block: BlockId) -> Result<Bytes> {
+        self.request("eth_getCode", json!([address, block])).await
+    }
+
+    /// Get the next nonce of an account (`eth_getTransactionCount`)
+    ///
+    /// With `pending`, transactions still in the mempool are counted, giving
+    /// the nonce for a new transaction; without, only mined ones are, giving
+    /// the nonce of the oldest stuck transaction. Setting that nonce on a
+    /// `TransactionRequest` with higher fees replaces the stuck transaction
+    /// (speed up, or cancel with a zero-value self-transfer). The nonce is
+    /// sent to the wallet as a hex quantity.
+    pub async fn get_nonce(&self, address: Address, pending: bool) -> Result<u64> {
+        let block = if pending { "pending" } else { "latest" };
+        let nonce: U64 = self
+            .request("eth_getTransactionCount", json!([address, block]))
+            .await?;
+        Ok(nonce.to())
+    }
+
+    /// Generate the EIP-2930 access list of a transaction (`eth_createAccessList`)
+    ///
+    /// The returned list can be set on `tx` before sending it, to make storage
+    /// accesses cheaper. Many wallet nodes don't implement the method and fail
+    /// with [`WindowError::MethodNotFound`] or [`WindowError::UnsupportedMethod`].
+    pub async fn create_access_list(&self, tx: &TransactionRequest) -> Result<AccessListResult> {
+        self.request("eth_createAccessList", json!([tx, "latest"]))
+            .await
+    }
+
+    /// Call a contract function from a `sol!` binding and decode its return value
+    ///
+    /// A low-ceremony alternative to `IContract::new(..).method().call()` for
+    /// one-off reads: `provider.call_decoded(token, IERC20::balanceOfCall { account }).await?`.
+    /// The call runs against the latest block. Reverts fail with
+    /// [`WindowError::ExecutionReverted`], whose
+    /// [`revert_reason`](WindowError::revert_reason) decodes the revert data.
+    pub async fn call_decoded<C: SolCall>(&self, to: Address, call: C) -> Result<C::Return> {
+        let output = self.eth_call(to, call.abi_encode().into()).await?;
+        Ok(C::abi_decode_returns(&output)?)
+    }
+
+    /// Execute `eth_call` against the latest block with just a target and calldata
+    pub(crate) async fn eth_call(&self, to: Address, data: Bytes) -> Result<Bytes> {
+        self.request("eth_call", json!([{ "to": to, "data": data }, "latest"]))
+            .await
+    }
+
+    /// Make any request with typed params and result
+    ///
+    /// Escape hatch for methods without a dedicated helper: `params` is
+    /// serialized to JSON, normalized and sent like any other request, and
+    /// the result is deserialized into `R`.
+    ///
+    /// ```rust,ignore
+    /// #[derive(serde::Deserialize)]
+    /// #[serde(rename_all = "camelCase")]
+    /// struct WalletInfo {
+    ///     name: String,
+    ///     version: String,
+    /// }
+    ///
+    /// let info: WalletInfo = provider.rpc("wallet_getInfo", ()).await?;
+    /// ```
+    ///
+    /// # Note
+    ///
+    /// `()` serializes to `null`, which is sent as empty params.
+    pub async fn rpc<P: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R> {
+        self.request(method, serde_json::to_value(params)?).await
+    }
+
+    /// Make a request and deserialize the result
+    pub(crate) async fn request<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<R> {
+        let result = self
+            .transport
+            .request_inner(method.to_string(), params)
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockWallet;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn hash() -> TxHash {
+        TxHash::repeat_byte(0xab)
+    }
+
+    #[wasm_bindgen_test]
+    async fn unknown_transaction_is_none() {
+        let wallet = MockWallet::new(|_, _| Ok(Value::Null));
+        let provider = WindowProvider::from_transport(wallet.transport());
+
+        assert!(provider.get_transaction(hash()).await.unwrap().is_none());
+        assert_eq!(
+            wallet.requests(),
+            vec![("eth_getTransactionByHash".to_string(), json!([hash()]))]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn returns_known_transaction() {
+        let wallet = MockWallet::new(|_, _| {
+            Ok(json!({
+                "hash": hash(),
+                "nonce": "0x7",
+                "blockHash": null,
+                "blockNumber": null,
+                "transactionIndex": null,
+                "from": "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045",
+                "to": "0x000000000000000000000000000000000000dEaD",
+                "value": "0xde0b6b3a7640000",
+                "gasPrice": "0x3b9aca00",
+                "gas": "0x5208",
+                "input": "0x",
+                "v": "0x25",
+                "r": B256::repeat_byte(0x11),
+                "s": B256::repeat_byte(0x22),
+                "type": "0x0",
+                "chainId": "0x1",
+            }))
+        });
+        let provider = WindowProvider::from_transport(wallet.transport());
+
+        let tx = provider.get_transaction(hash()).await.unwrap().unwrap();
+        assert_eq!(*tx.inner.tx_hash(), hash());
+        assert_eq!(tx.block_number, None);
+    }
+
+    #[wasm_bindgen_test]
+    async fn reads_storage_slots() {
+        let word = B256::with_last_byte(0x2a);
+        let wallet = MockWallet::new(move |_, _| Ok(json!(word)));
+        let provider = WindowProvider::from_transport(wallet.transport());
+        let address = Address::repeat_byte(0xaa);
+
+        let slot = U256::from(5);
+        let value = provider
+            .get_storage_at(address, slot, BlockId::latest())
+            .await
+            .unwrap();
+        assert_eq!(value, word);
+
+        // Mapping and array slots are hashes, sent as full-width quantities
+        let slot = U256::from_be_bytes([0xab; 32]);
+        provider
+            .get_storage_at(address, slot, BlockId::number(16))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            wallet.requests(),
+            vec![
+                (
+                    "eth_getStorageAt".to_string(),
+                    json!([address, "0x5", "latest"])
+                ),
+                (
+                    "eth_getStorageAt".to_string(),
+                    json!([address, format!("0x{}", "ab".repeat(32)), "0x10"])
+                ),
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn reads_empty_code() {
+        let wallet = MockWallet::new(|_, _| Ok(json!("0x")));
+        let provider = WindowProvider::from_transport(wallet.transport());
+        let address = Address::repeat_byte(0xaa);
+
+        let code = provider
+            .get_code_at(address, BlockId::latest())
+            .await
+            .unwrap();
+        assert!(code.is_empty());
+        assert_eq!(
+            wallet.requests(),
+            vec![("eth_getCode".to_string(), json!([address, "latest"]))]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn parses_nonstandard_chain_ids() {
+        for (reported, expected) in [(json!("0x89"), 137), (json!("137"), 137), (json!(10), 10)] {
+            let wallet = MockWallet::new(move |_, _| Ok(reported.clone()));
+            let provider = WindowProvider::from_transport(wallet.transport());
+            assert_eq!(provider.chain_id().await.unwrap(), expected);
+        }
+    }
+}
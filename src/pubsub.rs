@@ -0,0 +1,231 @@
+//! `eth_subscribe` subscriptions delivered through EIP-1193 `message` events
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use alloy_rpc_types_eth::{Filter, Log};
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures::future::{self, Either};
+use futures::stream::{self, Stream, StreamExt};
+use serde_json::{json, Value};
+use wasm_bindgen_futures::spawn_local;
+
+use crate::error::{Result, WindowError};
+use crate::events::{ProviderMessage, Subscription};
+use crate::provider::WindowProvider;
+use crate::transport::WindowTransport;
+
+/// What to subscribe to with [`WindowPubSub::subscribe`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SubscribeParams {
+    /// New block headers
+    NewHeads,
+    /// Logs matching a filter
+    Logs(Box<Filter>),
+    /// Hashes of transactions entering the node's mempool
+    NewPendingTransactions,
+}
+
+impl SubscribeParams {
+    /// Build the `eth_subscribe` params array
+    fn to_params(&self) -> Value {
+        match self {
+            SubscribeParams::NewHeads => json!(["newHeads"]),
+            SubscribeParams::Logs(filter) => json!(["logs", filter]),
+            SubscribeParams::NewPendingTransactions => json!(["newPendingTransactions"]),
+        }
+    }
+}
+
+/// Senders of the active subscriptions, keyed by subscription ID
+type Streams = Rc<RefCell<HashMap<String, UnboundedSender<Value>>>>;
+
+/// `eth_subscribe` support for wallets that implement it
+///
+/// A single `message` listener demultiplexes `eth_subscription` notifications
+/// to the stream of the matching subscription. Not every wallet supports
+/// subscriptions; `subscribe` fails with the wallet's error when it doesn't.
+#[derive(Clone, Debug)]
+pub struct WindowPubSub {
+    transport: WindowTransport,
+    streams: Streams,
+    _listener: Rc<Subscription>,
+}
+
+impl WindowPubSub {
+    /// Create a pubsub handle on top of a transport
+    pub fn new(transport: WindowTransport) -> Self {
+        let streams = Streams::default();
+
+        let listener_streams = streams.clone();
+        let listener = transport.on_message(move |message| {
+            if let ProviderMessage::EthSubscription {
+                subscription,
+                result,
+            } = message
+            {
+                if let Some(tx) = listener_streams.borrow().get(&subscription) {
+                    let _ = tx.unbounded_send(result);
+                }
+            }
+        });
+
+        Self {
+            transport,
+            streams,
+            _listener: Rc::new(listener),
+        }
+    }
+
+    /// Subscribe and stream the raw notification payloads
+    ///
+    /// Payloads are yielded as JSON (a header, a log or a transaction hash,
+    /// depending on `params`). Dropping the stream sends `eth_unsubscribe`.
+    pub async fn subscribe(&self, params: SubscribeParams) -> Result<SubscriptionStream> {
+        let id = self
+            .transport
+            .request_inner("eth_subscribe".to_string(), params.to_params())
+            .await?;
+        let id = id
+            .as_str()
+            .ok_or_else(|| WindowError::Rpc(format!("Invalid subscription ID: {}", id)))?
+            .to_string();
+
+        let (tx, rx) = mpsc::unbounded();
+        self.streams.borrow_mut().insert(id.clone(), tx);
+
+        Ok(SubscriptionStream {
+            id,
+            rx,
+            pubsub: self.clone(),
+        })
+    }
+}
+
+/// Notifications of one `eth_subscribe` subscription
+///
+/// Unsubscribes from the wallet when dropped.
+#[derive(Debug)]
+pub struct SubscriptionStream {
+    id: String,
+    rx: UnboundedReceiver<Value>,
+    pubsub: WindowPubSub,
+}
+
+impl SubscriptionStream {
+    /// Subscription ID assigned by the wallet
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Stream for SubscriptionStream {
+    type Item = Value;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Value>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for SubscriptionStream {
+    fn drop(&mut self) {
+        self.pubsub.streams.borrow_mut().remove(&self.id);
+
+        let transport = self.pubsub.transport.clone();
+        let id = self.id.clone();
+        spawn_local(async move {
+            if let Err(e) = transport
+                .request_inner("eth_unsubscribe".to_string(), json!([id]))
+                .await
+            {
+                tracing::debug!("eth_unsubscribe failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Item of [`WindowProvider::subscribe_logs`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogEvent {
+    /// A log matching the filter
+    Log(Box<Log>),
+    /// The wallet switched to the given chain
+    ///
+    /// Logs don't carry across chains, so state derived from earlier logs
+    /// should be reset. Logs after this one come from the new chain.
+    ChainChanged(u64),
+}
+
+/// State of a [`WindowProvider::subscribe_logs`] stream
+struct LogsState {
+    pubsub: WindowPubSub,
+    filter: Filter,
+    /// Subscription on the current chain, `None` after a chain change
+    stream: Option<SubscriptionStream>,
+    chains: UnboundedReceiver<u64>,
+    _listener: Subscription,
+}
+
+impl WindowProvider {
+    /// Stream logs matching `filter`, resubscribing when the chain changes
+    ///
+    /// Subscribes with `eth_subscribe("logs", filter)`. On `chainChanged` the
+    /// subscription is dropped (sending `eth_unsubscribe`),
+    /// [`LogEvent::ChainChanged`] is yielded and a new subscription is made
+    /// on the new chain. Fails if the wallet rejects the first subscription;
+    /// the stream ends if a resubscription fails.
+    pub async fn subscribe_logs(&self, filter: Filter) -> Result<impl Stream<Item = LogEvent>> {
+        let pubsub = WindowPubSub::new(self.transport().clone());
+
+        // Listen before subscribing so no chain change is missed
+        let (tx, chains) = mpsc::unbounded();
+        let listener = self.transport().on_chain_changed(move |chain_id| {
+            let _ = tx.unbounded_send(chain_id);
+        });
+        let stream = pubsub
+            .subscribe(SubscribeParams::Logs(Box::new(filter.clone())))
+            .await?;
+
+        let state = LogsState {
+            pubsub,
+            filter,
+            stream: Some(stream),
+            chains,
+            _listener: listener,
+        };
+        Ok(stream::unfold(state, |mut state| async move {
+            loop {
+                let Some(stream) = state.stream.as_mut() else {
+                    let params = SubscribeParams::Logs(Box::new(state.filter.clone()));
+                    match state.pubsub.subscribe(params).await {
+                        Ok(stream) => state.stream = Some(stream),
+                        Err(e) => {
+                            tracing::debug!("Log resubscription failed: {}", e);
+                            return None;
+                        }
+                    }
+                    continue;
+                };
+
+                let next = match future::select(stream.next(), state.chains.next()).await {
+                    Either::Left((log, _)) => Either::Left(log),
+                    Either::Right((chain_id, _)) => Either::Right(chain_id),
+                };
+                match next {
+                    Either::Left(Some(log)) => match serde_json::from_value(log) {
+                        Ok(log) => return Some((LogEvent::Log(Box::new(log)), state)),
+                        Err(e) => tracing::debug!("Ignoring unparseable log: {}", e),
+                    },
+                    Either::Right(Some(chain_id)) => {
+                        state.stream = None;
+                        return Some((LogEvent::ChainChanged(chain_id), state));
+                    }
+                    Either::Left(None) | Either::Right(None) => return None,
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,134 @@
+//! FIFO queue serializing wallet requests for single-flight mode
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::future::poll_fn;
+use std::rc::Rc;
+use std::task::{Poll, Waker};
+
+/// First-in first-out gate shared by all clones of a transport
+///
+/// Each request takes a ticket in issue order and runs only once every
+/// earlier ticket has finished or been dropped.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RequestQueue {
+    inner: Rc<RefCell<QueueInner>>,
+}
+
+#[derive(Debug, Default)]
+struct QueueInner {
+    /// Next ticket to hand out
+    next: u64,
+    /// Ticket currently allowed to run
+    serving: u64,
+    /// Wakers of tickets waiting for their turn
+    wakers: HashMap<u64, Waker>,
+    /// Tickets dropped before their turn came, skipped when reached
+    abandoned: BTreeSet<u64>,
+}
+
+impl QueueInner {
+    /// Hand the turn to the next live ticket
+    fn advance(&mut self) {
+        self.serving += 1;
+        while self.abandoned.remove(&self.serving) {
+            self.serving += 1;
+        }
+        if let Some(waker) = self.wakers.remove(&self.serving) {
+            waker.wake();
+        }
+    }
+}
+
+/// Place in the queue, releasing the turn when dropped
+pub(crate) struct Ticket {
+    queue: Rc<RefCell<QueueInner>>,
+    number: u64,
+}
+
+impl RequestQueue {
+    /// Wait until every earlier request has completed
+    pub(crate) async fn acquire(&self) -> Ticket {
+        let ticket = {
+            let mut inner = self.inner.borrow_mut();
+            let number = inner.next;
+            inner.next += 1;
+            Ticket {
+                queue: self.inner.clone(),
+                number,
+            }
+        };
+
+        poll_fn(|cx| {
+            let mut inner = ticket.queue.borrow_mut();
+            if inner.serving == ticket.number {
+                Poll::Ready(())
+            } else {
+                inner.wakers.insert(ticket.number, cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await;
+
+        ticket
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        let mut inner = self.queue.borrow_mut();
+        inner.wakers.remove(&self.number);
+        if inner.serving == self.number {
+            inner.advance();
+        } else {
+            inner.abandoned.insert(self.number);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::task::noop_waker_ref;
+    use futures::FutureExt;
+    use std::task::Context;
+
+    #[test]
+    fn serves_tickets_in_order() {
+        let queue = RequestQueue::default();
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        let first = queue.acquire().now_or_never().unwrap();
+        let mut second = Box::pin(queue.acquire());
+        let mut third = Box::pin(queue.acquire());
+        assert!(second.poll_unpin(&mut cx).is_pending());
+        assert!(third.poll_unpin(&mut cx).is_pending());
+
+        drop(first);
+        assert!(third.poll_unpin(&mut cx).is_pending());
+        let Poll::Ready(second) = second.poll_unpin(&mut cx) else {
+            panic!("second ticket not served after the first");
+        };
+        drop(second);
+        assert!(third.poll_unpin(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn skips_abandoned_tickets() {
+        let queue = RequestQueue::default();
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        let first = queue.acquire().now_or_never().unwrap();
+        let mut abandoned = Box::pin(queue.acquire());
+        let mut waiting = Box::pin(queue.acquire());
+        assert!(abandoned.poll_unpin(&mut cx).is_pending());
+        assert!(waiting.poll_unpin(&mut cx).is_pending());
+
+        drop(abandoned);
+        drop(first);
+        assert!(waiting.poll_unpin(&mut cx).is_ready());
+        // Later requests still get through once the queue drains
+        drop(waiting);
+        assert!(queue.acquire().now_or_never().is_some());
+    }
+}
@@ -0,0 +1,92 @@
+//! Bounded in-memory log of wallet requests for reproducing issues
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+
+use crate::error::{Result, WindowError};
+
+/// Ring buffer of the most recent requests, shared by all clones of a transport
+#[derive(Clone, Debug)]
+pub(crate) struct Recorder {
+    capacity: usize,
+    entries: Rc<RefCell<VecDeque<Value>>>,
+}
+
+impl Recorder {
+    /// Create a recorder keeping at most `capacity` requests
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            entries: Rc::new(RefCell::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// Record a request with the params sent to the wallet and its outcome
+    pub(crate) fn record(&self, method: &str, params: &Value, result: &Result<Value>) {
+        let mut entry = json!({ "method": method, "params": params });
+        match result {
+            Ok(result) => entry["result"] = result.clone(),
+            Err(e) => entry["error"] = error_json(e),
+        }
+
+        let mut entries = self.entries.borrow_mut();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Recorded requests, oldest first, as a JSON array
+    pub(crate) fn to_json(&self) -> Value {
+        Value::Array(self.entries.borrow().iter().cloned().collect())
+    }
+}
+
+/// JSON-RPC style `{ code, message }` object for a failed request
+fn error_json(e: &WindowError) -> Value {
+    let mut error = json!({ "message": e.to_string() });
+    if let Some(code) = e.code() {
+        error["code"] = json!(code);
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_most_recent_requests() {
+        let recorder = Recorder::new(2);
+        recorder.record("eth_chainId", &json!([]), &Ok(json!("0x1")));
+        recorder.record("eth_accounts", &json!([]), &Ok(json!([])));
+        recorder.record(
+            "eth_sendTransaction",
+            &json!([{}]),
+            &Err(WindowError::UserRejected),
+        );
+
+        assert_eq!(
+            recorder.to_json(),
+            json!([
+                { "method": "eth_accounts", "params": [], "result": [] },
+                {
+                    "method": "eth_sendTransaction",
+                    "params": [{}],
+                    "error": { "code": 4001, "message": WindowError::UserRejected.to_string() },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn keeps_at_least_one_request() {
+        let recorder = Recorder::new(0);
+        recorder.record("eth_chainId", &json!([]), &Ok(json!("0x1")));
+        assert_eq!(recorder.to_json().as_array().unwrap().len(), 1);
+    }
+}
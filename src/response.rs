@@ -0,0 +1,80 @@
+//! JSON-RPC responses assembled from wallet results
+
+use alloy_json_rpc::{ErrorPayload, Id, Response, ResponsePayload};
+use alloy_transport::TransportError;
+use serde_json::Value;
+
+use crate::error::WindowError;
+
+/// Build a successful response, keeping the request ID exactly as alloy issued it
+pub(crate) fn success_response(
+    id: Id,
+    result: &Value,
+) -> std::result::Result<Response, TransportError> {
+    let result = serde_json::value::to_raw_value(result).map_err(TransportError::local_usage)?;
+    Ok(Response {
+        id,
+        payload: ResponsePayload::Success(result),
+    })
+}
+
+/// Build an error response for a failed request in a batch
+///
+/// Wallet errors keep their code, others are reported as -32000.
+pub(crate) fn error_response(id: Id, e: &WindowError) -> Response {
+    let payload = e.to_error_payload().unwrap_or_else(|| ErrorPayload {
+        code: -32000,
+        message: e.to_string().into(),
+        data: None,
+    });
+    Response {
+        id,
+        payload: ResponsePayload::Failure(payload),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ids() -> [(Id, Value); 3] {
+        [
+            (Id::String("req-1".to_string()), json!("req-1")),
+            (Id::Number(7), json!(7)),
+            (Id::None, Value::Null),
+        ]
+    }
+
+    #[test]
+    fn success_keeps_request_ids() {
+        for (id, expected) in ids() {
+            let response = success_response(id.clone(), &json!("0x1")).unwrap();
+            assert_eq!(response.id, id);
+            let response = serde_json::to_value(&response).unwrap();
+            assert_eq!(response["id"], expected);
+            assert_eq!(response["result"], "0x1");
+        }
+    }
+
+    #[test]
+    fn error_keeps_request_ids() {
+        for (id, expected) in ids() {
+            let response = error_response(id.clone(), &WindowError::UserRejected);
+            assert_eq!(response.id, id);
+            let response = serde_json::to_value(&response).unwrap();
+            assert_eq!(response["id"], expected);
+            assert_eq!(response["error"]["code"], 4001);
+        }
+    }
+
+    #[test]
+    fn error_defaults_to_server_error_code() {
+        let response = error_response(Id::Number(1), &WindowError::NoWallet);
+        let ResponsePayload::Failure(payload) = response.payload else {
+            panic!("expected a failure payload");
+        };
+        assert_eq!(payload.code, -32000);
+        assert_eq!(payload.message, WindowError::NoWallet.to_string());
+    }
+}
@@ -0,0 +1,262 @@
+//! Signing request params and parsing of the signatures wallets return
+
+#[cfg(feature = "eip712")]
+use alloy_primitives::U256;
+use alloy_primitives::{Address, Signature};
+use serde_json::{json, Value};
+
+use crate::error::{Result, WindowError};
+
+/// JSON-RPC method used by [`WindowSigner::sign_bytes`](crate::WindowSigner::sign_bytes)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignMethod {
+    /// `personal_sign` (EIP-191): the wallet prepends
+    /// `"\x19Ethereum Signed Message:\n" + len` before hashing, so the
+    /// signature can never be a valid transaction signature
+    PersonalSign,
+    /// `eth_sign`: the wallet signs the raw 32-byte payload with no prefix.
+    /// Most wallets disable it (MetaMask removed it) because it can be used
+    /// to sign arbitrary transactions.
+    EthSign,
+}
+
+impl SignMethod {
+    /// JSON-RPC method name
+    pub fn method(&self) -> &'static str {
+        match self {
+            SignMethod::PersonalSign => "personal_sign",
+            SignMethod::EthSign => "eth_sign",
+        }
+    }
+
+    /// Build the params array for signing `bytes` with `address`
+    ///
+    /// Note the order differs: `personal_sign` takes `[data, address]` while
+    /// `eth_sign` takes `[address, data]`.
+    pub fn params(&self, address: Address, bytes: &[u8]) -> Value {
+        let data = format!("0x{}", hex::encode(bytes));
+        match self {
+            SignMethod::PersonalSign => json!([data, address.to_string()]),
+            SignMethod::EthSign => json!([address.to_string(), data]),
+        }
+    }
+}
+
+/// Entry of legacy `eth_signTypedData` (v1) data
+///
+/// v1 predates EIP-712 structs and domains: the data is a flat list of typed
+/// values, e.g. `{ type: "string", name: "Message", value: "Hi" }`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct TypedDataV1Entry {
+    /// Solidity type of the value, e.g. `string` or `uint32`
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Name of the value
+    pub name: String,
+    /// The value, as a JSON string, number or boolean
+    pub value: Value,
+}
+
+/// Build the `eth_signTypedData` (v1) params array
+///
+/// v1 takes `[data, address]`, the reverse of the v3/v4 order.
+pub(crate) fn typed_data_v1_params(entries: &[TypedDataV1Entry], address: Address) -> Value {
+    json!([entries, address])
+}
+
+/// Parse a hex signature returned by the wallet
+///
+/// A few wallets omit the `0x` prefix, write it as `0X` or pad the string
+/// with whitespace, so normalize before parsing.
+pub(crate) fn parse_signature(sig_hex: &str) -> Result<Signature> {
+    let sig_hex = sig_hex.trim();
+    let digits = sig_hex
+        .strip_prefix("0x")
+        .or_else(|| sig_hex.strip_prefix("0X"))
+        .unwrap_or(sig_hex);
+
+    format!("0x{}", digits)
+        .parse::<Signature>()
+        .map_err(|e| WindowError::InvalidSignature(e.to_string()))
+}
+
+/// Parse a typed data signature given as a hex string or as `{ r, s, v }`
+///
+/// A few wallets return the components instead of the concatenated
+/// signature. `r` and `s` must be 32-byte hex strings; `v` may be a number
+/// or a hex string and must be 0, 1, 27 or 28.
+#[cfg(feature = "eip712")]
+pub(crate) fn parse_signature_value(value: &Value) -> Result<Signature> {
+    let invalid = WindowError::InvalidSignature;
+
+    let obj = match value {
+        Value::String(sig_hex) => return parse_signature(sig_hex),
+        Value::Object(obj) => obj,
+        _ => return Err(invalid(format!("unexpected value {}", value))),
+    };
+
+    let component = |name: &str| -> Result<U256> {
+        let hex = obj
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid(format!("missing `{}`", name)))?;
+        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+        if digits.len() != 64 {
+            return Err(invalid(format!("`{}` must be 32 bytes", name)));
+        }
+        U256::from_str_radix(digits, 16).map_err(|e| invalid(format!("`{}`: {}", name, e)))
+    };
+    let r = component("r")?;
+    let s = component("s")?;
+
+    let v = match obj.get("v") {
+        Some(Value::Number(v)) => v.as_u64(),
+        Some(Value::String(v)) => {
+            let digits = v.strip_prefix("0x").unwrap_or(v);
+            u64::from_str_radix(digits, if digits.len() < v.len() { 16 } else { 10 }).ok()
+        }
+        _ => None,
+    };
+    let y_parity = match v {
+        Some(0 | 27) => false,
+        Some(1 | 28) => true,
+        _ => {
+            return Err(invalid(format!(
+                "unsupported `v` {}",
+                obj.get("v").unwrap_or(&Value::Null)
+            )))
+        }
+    };
+
+    Ok(Signature::new(r, s, y_parity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+
+    fn signature_hex() -> String {
+        format!("{}{}1b", "11".repeat(32), "22".repeat(32))
+    }
+
+    fn expected() -> Signature {
+        Signature::new(
+            U256::from_be_bytes([0x11; 32]),
+            U256::from_be_bytes([0x22; 32]),
+            false,
+        )
+    }
+
+    #[test]
+    fn builds_sign_params() {
+        let address = Address::repeat_byte(0xaa);
+        assert_eq!(SignMethod::PersonalSign.method(), "personal_sign");
+        assert_eq!(SignMethod::EthSign.method(), "eth_sign");
+        assert_eq!(
+            SignMethod::PersonalSign.params(address, b"hi"),
+            json!(["0x6869", address.to_string()])
+        );
+        assert_eq!(
+            SignMethod::EthSign.params(address, b"hi"),
+            json!([address.to_string(), "0x6869"])
+        );
+    }
+
+    #[test]
+    fn parses_prefixed_signatures() {
+        assert_eq!(
+            parse_signature(&format!("0x{}", signature_hex())).unwrap(),
+            expected()
+        );
+        assert_eq!(
+            parse_signature(&format!("0X{}", signature_hex())).unwrap(),
+            expected()
+        );
+    }
+
+    #[test]
+    fn parses_signatures_without_prefix() {
+        assert_eq!(parse_signature(&signature_hex()).unwrap(), expected());
+        assert_eq!(
+            parse_signature(&format!(" {}\n", signature_hex())).unwrap(),
+            expected()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        for invalid in [
+            "",
+            "0x",
+            "0x1234",
+            &format!("0x{}zz", &signature_hex()[2..]),
+        ] {
+            assert!(
+                matches!(
+                    parse_signature(invalid),
+                    Err(WindowError::InvalidSignature(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[cfg(feature = "eip712")]
+    #[test]
+    fn parses_signature_components() {
+        let r = format!("0x{}", "11".repeat(32));
+        let s = format!("0x{}", "22".repeat(32));
+        for v in [json!(27), json!("0x1b"), json!("27"), json!(0)] {
+            let value = json!({ "r": r, "s": s, "v": v });
+            assert_eq!(parse_signature_value(&value).unwrap(), expected());
+        }
+        assert_eq!(
+            parse_signature_value(&json!(signature_hex())).unwrap(),
+            expected()
+        );
+        for invalid in [
+            json!({ "r": r, "s": s, "v": 2 }),
+            json!({ "r": "0x11", "s": s, "v": 27 }),
+            json!({ "r": r, "v": 27 }),
+            json!(27),
+        ] {
+            assert!(
+                matches!(
+                    parse_signature_value(&invalid),
+                    Err(WindowError::InvalidSignature(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn builds_typed_data_v1_params() {
+        let address = Address::repeat_byte(0xaa);
+        let entries = [
+            TypedDataV1Entry {
+                ty: "string".to_string(),
+                name: "Message".to_string(),
+                value: json!("Hi"),
+            },
+            TypedDataV1Entry {
+                ty: "uint32".to_string(),
+                name: "Amount".to_string(),
+                value: json!(42),
+            },
+        ];
+        assert_eq!(
+            typed_data_v1_params(&entries, address),
+            json!([
+                [
+                    { "type": "string", "name": "Message", "value": "Hi" },
+                    { "type": "uint32", "name": "Amount", "value": 42 },
+                ],
+                format!("0x{}", "aa".repeat(20)),
+            ])
+        );
+    }
+}
@@ -1,120 +1,548 @@
 //! WindowSigner implementation - delegates signing to browser wallet
 
+use std::collections::HashMap;
+use std::time::Duration;
+
 use alloy_primitives::{Address, Signature, B256};
 use alloy_signer::{Result as SignerResult, Signer, UnsupportedSignerOperation};
-use serde_json::json;
+use futures::future::{self, Either};
+use serde_json::{json, Value};
 use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::JsFuture;
 
 #[cfg(feature = "eip712")]
 use alloy_dyn_abi::eip712::TypedData;
 #[cfg(feature = "eip712")]
 use alloy_dyn_abi::Eip712Domain;
 #[cfg(feature = "eip712")]
+use alloy_primitives::U256;
+#[cfg(feature = "eip712")]
 use alloy_sol_types::SolStruct;
 
-use crate::error::{Result, WindowError};
-
-/// Get window.ethereum object and make requests
-#[wasm_bindgen(inline_js = r#"
-export function get_ethereum() {
-    if (typeof window !== 'undefined' && window.ethereum) {
-        return window.ethereum;
-    }
-    return null;
-}
+#[cfg(feature = "eip712")]
+use crate::typed_data::validate_typed_data;
 
-export function ethereum_request(ethereum, method, params) {
-    return ethereum.request({ method, params });
+use crate::accounts::parse_accounts;
+use crate::capabilities::{Capabilities, CapabilityCache, ChainCapabilities};
+use crate::discovery::{ProviderDetail, ProviderInfo};
+use crate::error::{Result, WindowError};
+use crate::js_bindings::sleep;
+use crate::normalize::parse_chain_id;
+#[cfg(feature = "eip712")]
+use crate::signature::parse_signature_value;
+use crate::signature::{parse_signature, typed_data_v1_params, SignMethod, TypedDataV1Entry};
+use crate::state::{ProviderState, SharedState};
+use crate::transport::WindowTransport;
+
+/// Session state returned by [`WindowSigner::connect`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// All accounts exposed to the site, the active account first
+    pub accounts: Vec<Address>,
+    /// Chain ID the wallet is connected to
+    pub chain_id: u64,
+    /// EIP-5792 capabilities of the active account (empty if unsupported)
+    pub capabilities: Capabilities,
 }
-"#)]
-extern "C" {
-    #[wasm_bindgen(js_name = get_ethereum)]
-    fn get_ethereum() -> JsValue;
 
-    #[wasm_bindgen(js_name = ethereum_request)]
-    fn ethereum_request(ethereum: &JsValue, method: &str, params: &JsValue) -> js_sys::Promise;
+/// The site's `eth_accounts` permission as reported by `wallet_getPermissions`
+enum AccountsPermission {
+    /// The wallet doesn't implement `wallet_getPermissions`
+    Unsupported,
+    /// The site holds no `eth_accounts` permission
+    NotGranted,
+    /// Granted without an account restriction
+    Unrestricted,
+    /// Granted for these accounts only
+    Restricted(Vec<Address>),
 }
 
 /// Signer that delegates to window.ethereum (EIP-1193)
+///
+/// Wallet requests go through a [`WindowTransport`], so its options (e.g.
+/// [`with_max_concurrency`](WindowTransport::with_max_concurrency) or
+/// [`single_flight`](WindowTransport::single_flight)) and its disconnect
+/// handling, failure logging and recording cover signing requests too.
 #[derive(Clone, Debug)]
 pub struct WindowSigner {
-    ethereum: JsValue,
-    address: Address,
-    chain_id: Option<u64>,
+    transport: WindowTransport,
+    info: Option<ProviderInfo>,
+    cache: CapabilityCache,
 }
 
 impl WindowSigner {
     /// Create a new WindowSigner and request account access
     pub async fn new() -> Result<Self> {
-        let ethereum = get_ethereum();
+        Self::from_transport(WindowTransport::new()?).await
+    }
 
-        if ethereum.is_null() || ethereum.is_undefined() {
-            return Err(WindowError::NoWallet);
+    /// Request account access through an existing transport
+    ///
+    /// The signer sends its requests through `transport` and shares its
+    /// state, so a transport configured with a concurrency limit, a
+    /// single-flight queue or a recorder applies it to signing requests as
+    /// well.
+    pub async fn from_transport(transport: WindowTransport) -> Result<Self> {
+        // Request accounts (will trigger wallet popup)
+        let accounts = fetch_accounts(&transport, "eth_requestAccounts").await?;
+        let chain_id = fetch_chain_id(&transport).await?;
+
+        Self::with_state(transport, accounts, chain_id)
+    }
+
+    /// Like [`new`](Self::new), but give up if the user doesn't answer in time
+    ///
+    /// Returns [`WindowError::Timeout`] once `timeout` elapses without the
+    /// account prompt being answered, so the UI can reset its connect button.
+    ///
+    /// # Note
+    /// The wallet's prompt can't be closed from the page and stays open after
+    /// the timeout. Requesting accounts again while it is open usually fails
+    /// with -32002 (request already pending) until the user answers or
+    /// dismisses it.
+    pub async fn new_with_timeout(timeout: Duration) -> Result<Self> {
+        let connect = Box::pin(Self::new());
+        let timer = Box::pin(sleep(timeout));
+
+        match future::select(connect, timer).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(WindowError::Timeout(timeout)),
         }
+    }
+
+    /// Get the connected address without requesting permissions again
+    pub async fn from_existing() -> Result<Self> {
+        let transport = WindowTransport::new()?;
+
+        // Get accounts (doesn't prompt)
+        let accounts = fetch_accounts(&transport, "eth_accounts").await?;
+        let chain_id = fetch_chain_id(&transport).await?;
+
+        Self::with_state(transport, accounts, chain_id)
+    }
+
+    /// Restore a previously authorized session without prompting
+    ///
+    /// Returns `Ok(None)` when the wallet exposes no accounts - it is locked
+    /// or the site was never authorized - so apps can silently restore
+    /// sessions on load. `Err` is reserved for actual failures, such as a
+    /// missing wallet or a failed request.
+    pub async fn try_restore() -> Result<Option<Self>> {
+        match Self::from_existing().await {
+            Ok(signer) => Ok(Some(signer)),
+            Err(WindowError::NoAccounts) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Request account access and return the signer with the full session state
+    ///
+    /// After the account prompt resolves, the chain ID and the EIP-5792
+    /// capabilities are fetched concurrently. Wallets that don't implement
+    /// `wallet_getCapabilities` report empty capabilities.
+    pub async fn connect() -> Result<(Self, ConnectionInfo)> {
+        let transport = WindowTransport::new()?;
 
         // Request accounts (will trigger wallet popup)
-        let params = serde_wasm_bindgen::to_value(&json!([]))?;
-        let promise = ethereum_request(&ethereum, "eth_requestAccounts", &params);
-        let result = JsFuture::from(promise).await?;
-        let accounts: Vec<String> = serde_wasm_bindgen::from_value(result)?;
+        let accounts = fetch_accounts(&transport, "eth_requestAccounts").await?;
+        let address = *accounts.first().ok_or(WindowError::NoAccounts)?;
+
+        let (chain_id, capabilities) = futures::join!(
+            fetch_chain_id(&transport),
+            fetch_capabilities(&transport, address)
+        );
+        let chain_id = chain_id?.ok_or_else(|| WindowError::Rpc("Invalid chain ID".to_string()))?;
+
+        let signer = Self::with_state(transport, accounts.clone(), Some(chain_id))?;
+        signer.cache.set_capabilities(address, capabilities.typed());
+        let info = ConnectionInfo {
+            accounts,
+            chain_id,
+            capabilities,
+        };
 
-        let address = accounts
-            .first()
-            .ok_or(WindowError::NoAccounts)?
-            .parse()
-            .map_err(|e| WindowError::InvalidAddress(format!("{}", e)))?;
+        Ok((signer, info))
+    }
 
-        // Get chain ID
-        let chain_params = serde_wasm_bindgen::to_value(&json!([]))?;
-        let chain_promise = ethereum_request(&ethereum, "eth_chainId", &chain_params);
-        let chain_result = JsFuture::from(chain_promise).await?;
-        let chain_id_hex: String = serde_wasm_bindgen::from_value(chain_result)?;
+    /// Request account access from a wallet found by EIP-6963 discovery
+    ///
+    /// Uses the announced provider instead of window.ethereum, which only
+    /// holds whichever wallet injected itself last. The announced metadata is
+    /// kept and available from [`provider_info`](Self::provider_info).
+    pub async fn from_provider(detail: ProviderDetail) -> Result<Self> {
+        let ProviderDetail { info, provider } = detail;
+        let state = SharedState::new(&provider, ProviderState::default());
+        let transport = WindowTransport::with_state(provider, state);
+
+        let accounts = fetch_accounts(&transport, "eth_requestAccounts").await?;
+        let chain_id = fetch_chain_id(&transport).await?;
+
+        let mut signer = Self::with_state(transport, accounts, chain_id)?;
+        signer.info = Some(info);
+        Ok(signer)
+    }
 
-        let chain_id = u64::from_str_radix(chain_id_hex.trim_start_matches("0x"), 16).ok();
+    /// Create a signer for a known address without contacting the wallet
+    ///
+    /// Skips the `eth_requestAccounts`/`eth_chainId` handshake, e.g. to restore
+    /// a session persisted by the app without a prompt or round-trip on reload.
+    ///
+    /// # Note
+    /// The caller is trusted: nothing checks that `address` is authorized for
+    /// the site or that `chain_id` matches the wallet. If it's wrong, signing
+    /// requests fail or the wallet signs with its active account instead.
+    /// Account and chain events still update the state from then on.
+    pub fn from_address(ethereum: JsValue, address: Address, chain_id: Option<u64>) -> Self {
+        let state = SharedState::new(
+            &ethereum,
+            ProviderState {
+                address: Some(address),
+                accounts: vec![address],
+                chain_id,
+                disconnected: None,
+            },
+        );
+
+        let transport = WindowTransport::with_state(ethereum, state);
+
+        Self {
+            cache: CapabilityCache::new(&transport),
+            transport,
+            info: None,
+        }
+    }
+
+    /// Create a signer whose state is kept current by wallet events
+    fn with_state(
+        transport: WindowTransport,
+        accounts: Vec<Address>,
+        chain_id: Option<u64>,
+    ) -> Result<Self> {
+        let address = *accounts.first().ok_or(WindowError::NoAccounts)?;
+        transport.shared_state().update(|state| {
+            state.address = Some(address);
+            state.accounts = accounts;
+            state.chain_id = chain_id;
+        });
 
         Ok(Self {
-            ethereum,
-            address,
-            chain_id,
+            cache: CapabilityCache::new(&transport),
+            transport,
+            info: None,
         })
     }
 
-    /// Get the connected address without requesting permissions again
-    pub async fn from_existing() -> Result<Self> {
-        let ethereum = get_ethereum();
+    /// Get the live account and chain state, shared by all clones of this signer
+    pub fn state(&self) -> ProviderState {
+        self.transport.state()
+    }
 
-        if ethereum.is_null() || ethereum.is_undefined() {
-            return Err(WindowError::NoWallet);
-        }
+    /// Name, icon and rdns the wallet announced, if created with
+    /// [`from_provider`](Self::from_provider)
+    pub fn provider_info(&self) -> Option<ProviderInfo> {
+        self.info.clone()
+    }
 
-        // Get accounts (doesn't prompt)
-        let params = serde_wasm_bindgen::to_value(&json!([]))?;
-        let promise = ethereum_request(&ethereum, "eth_accounts", &params);
-        let result = JsFuture::from(promise).await?;
-        let accounts: Vec<String> = serde_wasm_bindgen::from_value(result)?;
+    /// Get the transport this signer sends its requests through
+    ///
+    /// It shares the signer's wallet, live state and transport options.
+    pub fn transport(&self) -> WindowTransport {
+        self.transport.clone()
+    }
 
-        let address = accounts
-            .first()
-            .ok_or(WindowError::NoAccounts)?
-            .parse()
-            .map_err(|e| WindowError::InvalidAddress(format!("{}", e)))?;
+    /// Best-effort check whether the active account can sign
+    ///
+    /// Reads `wallet_getPermissions` (EIP-2255) and returns `false` when the
+    /// site holds no `eth_accounts` permission for the active account. Wallets
+    /// without the method are assumed to be able to sign.
+    ///
+    /// # Note
+    /// Wallets don't advertise watch-only accounts, so this can't catch every
+    /// case; signing requests they refuse fail with
+    /// [`WindowError::WatchOnlyAccount`].
+    pub async fn can_sign(&self) -> Result<bool> {
+        let Some(address) = self.state().address else {
+            return Ok(false);
+        };
+
+        Ok(match self.accounts_permission().await? {
+            AccountsPermission::Unsupported | AccountsPermission::Unrestricted => true,
+            AccountsPermission::NotGranted => false,
+            AccountsPermission::Restricted(accounts) => accounts.contains(&address),
+        })
+    }
 
-        // Get chain ID
-        let chain_params = serde_wasm_bindgen::to_value(&json!([]))?;
-        let chain_promise = ethereum_request(&ethereum, "eth_chainId", &chain_params);
-        let chain_result = JsFuture::from(chain_promise).await?;
-        let chain_id_hex: String = serde_wasm_bindgen::from_value(chain_result)?;
+    /// Get the accounts exposed to the site with `eth_accounts`
+    pub async fn accounts(&self) -> Result<Vec<Address>> {
+        fetch_accounts(&self.transport, "eth_accounts").await
+    }
 
-        let chain_id = u64::from_str_radix(chain_id_hex.trim_start_matches("0x"), 16).ok();
+    /// Get the accounts the site is permitted to use (EIP-2255)
+    ///
+    /// Reads the `restrictReturnedAccounts` caveat of the `eth_accounts`
+    /// permission from `wallet_getPermissions`, which may be a subset of the
+    /// wallet's accounts. Returns an empty list when the site holds no
+    /// `eth_accounts` permission; more accounts can be requested with
+    /// `wallet_requestPermissions`. Wallets without the method, or without a
+    /// caveat, fall back to [`accounts`](Self::accounts).
+    pub async fn permitted_accounts(&self) -> Result<Vec<Address>> {
+        match self.accounts_permission().await? {
+            AccountsPermission::Unsupported | AccountsPermission::Unrestricted => {
+                self.accounts().await
+            }
+            AccountsPermission::NotGranted => Ok(Vec::new()),
+            AccountsPermission::Restricted(accounts) => Ok(accounts),
+        }
+    }
 
-        Ok(Self {
-            ethereum,
-            address,
-            chain_id,
+    /// Read the site's `eth_accounts` permission from `wallet_getPermissions`
+    async fn accounts_permission(&self) -> Result<AccountsPermission> {
+        let result = match self.request("wallet_getPermissions", json!([])).await {
+            Ok(result) => result,
+            Err(WindowError::MethodNotFound(_)) | Err(WindowError::UnsupportedMethod(_)) => {
+                return Ok(AccountsPermission::Unsupported)
+            }
+            Err(e) => return Err(e),
+        };
+        let permissions: Vec<Value> = serde_json::from_value(result)?;
+
+        let Some(eth_accounts) = permissions
+            .iter()
+            .find(|p| p["parentCapability"] == "eth_accounts")
+        else {
+            return Ok(AccountsPermission::NotGranted);
+        };
+
+        // MetaMask lists the permitted accounts in a restrictReturnedAccounts caveat
+        let restricted = eth_accounts["caveats"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|caveat| caveat["type"] == "restrictReturnedAccounts");
+        Ok(match restricted {
+            Some(caveat) => AccountsPermission::Restricted(parse_accounts(&caveat["value"])?),
+            None => AccountsPermission::Unrestricted,
         })
     }
 
+    /// Get the EIP-5792 capabilities of the active account, keyed by chain ID
+    ///
+    /// Wallets that don't implement `wallet_getCapabilities` yield an empty
+    /// map, so "no capabilities" can be handled uniformly. Other failures are
+    /// returned as errors. The result is cached per account until the wallet
+    /// switches chains, so later calls don't reach the wallet.
+    pub async fn get_capabilities(&self) -> Result<HashMap<u64, ChainCapabilities>> {
+        let address = self.address();
+        if let Some(capabilities) = self.cache.capabilities(address) {
+            return Ok(capabilities);
+        }
+
+        let params = json!([address]);
+        let capabilities = match self.request("wallet_getCapabilities", params).await {
+            Ok(result) => Capabilities::from_response(result),
+            Err(WindowError::UnsupportedMethod(_)) | Err(WindowError::MethodNotFound(_)) => {
+                Capabilities::default()
+            }
+            Err(e) => return Err(e),
+        };
+
+        let capabilities = capabilities.typed();
+        self.cache.set_capabilities(address, capabilities.clone());
+        Ok(capabilities)
+    }
+
+    /// Whether the wallet executes `wallet_sendCalls` batches atomically on
+    /// the current chain, from the cached capabilities when available
+    pub async fn supports_atomic_batch(&self) -> Result<bool> {
+        let Some(chain_id) = self.chain_id() else {
+            return Ok(false);
+        };
+        Ok(self
+            .get_capabilities()
+            .await?
+            .get(&chain_id)
+            .is_some_and(|caps| caps.atomic_batch))
+    }
+
+    /// Revoke site permissions with EIP-2255 `wallet_revokePermissions`
+    ///
+    /// Pass `["eth_accounts"]` to disconnect the site. The wallet expects a
+    /// single object keyed by capability, e.g. `[{ "eth_accounts": {} }]`,
+    /// which is built from `caps`.
+    ///
+    /// # Note
+    /// MetaMask supports this method; most other injected wallets
+    /// don't yet. They fail with [`WindowError::UnsupportedMethod`], in which
+    /// case apps should fall back to clearing their own session state.
+    pub async fn revoke_permissions(&self, caps: Vec<String>) -> Result<()> {
+        let permissions: serde_json::Map<String, Value> =
+            caps.into_iter().map(|cap| (cap, json!({}))).collect();
+
+        match self
+            .request("wallet_revokePermissions", json!([permissions]))
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(WindowError::MethodNotFound(message)) => Err(WindowError::UnsupportedMethod(
+                format!("wallet_revokePermissions: {}", message),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Sign arbitrary bytes with the given method
+    ///
+    /// [`Signer::sign_message`] uses [`SignMethod::PersonalSign`] and
+    /// [`Signer::sign_hash`] uses [`SignMethod::EthSign`].
+    pub async fn sign_bytes(&self, bytes: &[u8], method: SignMethod) -> SignerResult<Signature> {
+        let sig_hex = self
+            .request_signature(self.address(), bytes, method)
+            .await
+            .map_err(|e| match e {
+                // Most wallets have disabled eth_sign
+                WindowError::UnsupportedMethod(_) | WindowError::MethodNotFound(_)
+                    if method == SignMethod::EthSign =>
+                {
+                    alloy_signer::Error::UnsupportedOperation(UnsupportedSignerOperation::SignHash)
+                }
+                e => alloy_signer::Error::other(e.to_string()),
+            })?;
+
+        parse_signature(&sig_hex).map_err(|e| alloy_signer::Error::other(e.to_string()))
+    }
+
+    /// Sign a message with `personal_sign` using a specific connected account
+    ///
+    /// Lets dapps sign with any of the accounts exposed to the site without
+    /// rebuilding the signer. Returns [`WindowError::NoAccounts`] if `address`
+    /// isn't among the connected accounts.
+    pub async fn sign_message_with(&self, address: Address, message: &[u8]) -> Result<Signature> {
+        if !self.state().accounts.contains(&address) {
+            return Err(WindowError::NoAccounts);
+        }
+
+        let sig_hex = self
+            .request_signature(address, message, SignMethod::PersonalSign)
+            .await?;
+        parse_signature(&sig_hex)
+    }
+
+    /// Sign several messages with `personal_sign`, one prompt after another
+    ///
+    /// Requests are issued sequentially so the prompts appear in order. The
+    /// first failure (typically a rejection) stops the sequence and is
+    /// returned as [`WindowError::SequenceFailed`] with the index of the
+    /// message that failed.
+    pub async fn sign_messages(&self, messages: &[&[u8]]) -> Result<Vec<Signature>> {
+        let mut signatures = Vec::with_capacity(messages.len());
+        for (index, message) in messages.iter().enumerate() {
+            let signature = self
+                .request_signature(self.address(), message, SignMethod::PersonalSign)
+                .await
+                .and_then(|sig_hex| parse_signature(&sig_hex))
+                .map_err(|e| WindowError::SequenceFailed {
+                    index,
+                    source: Box::new(e),
+                })?;
+            signatures.push(signature);
+        }
+        Ok(signatures)
+    }
+
+    /// Sign a message with `personal_sign`, returning the chain it was signed on
+    ///
+    /// The chain ID is the signer's tracked chain (asked from the wallet if
+    /// not known yet), so an off-chain verifier can reject signatures made
+    /// for another chain and prevent cross-chain replay. Fails with
+    /// [`WindowError::ChainMismatch`] if the wallet switched chains while the
+    /// prompt was open.
+    ///
+    /// # Note
+    /// An EIP-191 signature doesn't commit to a chain by itself: the verifier
+    /// can only trust the returned chain ID if `message` states it too (as
+    /// EIP-4361 "Sign-In with Ethereum" messages do), and should check the
+    /// two match before accepting the signature.
+    pub async fn sign_chain_bound_message(&self, message: &[u8]) -> Result<(Signature, u64)> {
+        let chain_id = match self.chain_id() {
+            Some(chain_id) => chain_id,
+            None => fetch_chain_id(&self.transport)
+                .await?
+                .ok_or_else(|| WindowError::Rpc("Invalid chain ID".to_string()))?,
+        };
+
+        let sig_hex = self
+            .request_signature(self.address(), message, SignMethod::PersonalSign)
+            .await?;
+        let signature = parse_signature(&sig_hex)?;
+
+        // The user may have switched chains while the prompt was open
+        match self.chain_id() {
+            Some(actual) if actual != chain_id => Err(WindowError::ChainMismatch {
+                expected: chain_id,
+                actual,
+            }),
+            _ => Ok((signature, chain_id)),
+        }
+    }
+
+    /// Sign legacy typed data with `eth_signTypedData` (v1)
+    ///
+    /// For old contracts that verify v1 signatures; prefer EIP-712 otherwise.
+    /// Support is limited to a few wallets (MetaMask among them).
+    ///
+    /// # Note
+    /// The param order is the reverse of `eth_signTypedData_v4`: v1 takes
+    /// `[data, address]` while v4 takes `[address, data]`.
+    pub async fn sign_typed_data_v1(&self, entries: Vec<TypedDataV1Entry>) -> Result<Signature> {
+        let params = typed_data_v1_params(&entries, self.address());
+        let result = self.request("eth_signTypedData", params).await?;
+        let sig_hex: String = serde_json::from_value(result)?;
+
+        parse_signature(&sig_hex)
+    }
+
+    /// Request a hex signature over `bytes` by `address` from the wallet
+    ///
+    /// A `personal_sign` rejected for its size fails with
+    /// [`WindowError::PayloadTooLarge`].
+    async fn request_signature(
+        &self,
+        address: Address,
+        bytes: &[u8],
+        method: SignMethod,
+    ) -> Result<String> {
+        let result = self
+            .request(method.method(), method.params(address, bytes))
+            .await
+            .map_err(|e| match e {
+                // Some wallets cap the size of personal_sign messages
+                e if method == SignMethod::PersonalSign => e.into_payload_too_large(),
+                e => e,
+            })?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Build an EIP-712 domain bound to the wallet's current chain
+    ///
+    /// The `chainId` is taken from the live signer state, so the domain always
+    /// matches the chain the wallet will check it against - a mismatch is the
+    /// most common reason wallets reject typed-data requests. Pair it with
+    /// [`sign_eip712`](Self::sign_eip712):
+    ///
+    /// ```rust,ignore
+    /// let domain = signer.domain("Permit2", "1", verifying_contract);
+    /// let signature = signer.sign_eip712(&permit, domain).await?;
+    /// ```
+    #[cfg(feature = "eip712")]
+    pub fn domain(&self, name: &str, version: &str, verifying_contract: Address) -> Eip712Domain {
+        Eip712Domain::new(
+            Some(name.to_string().into()),
+            Some(version.to_string().into()),
+            self.chain_id().map(U256::from),
+            Some(verifying_contract),
+            None,
+        )
+    }
+
     /// Sign statically-typed EIP-712 data by converting it to [`TypedData`] and
     /// delegating to `eth_signTypedData_v4`.
     ///
@@ -137,69 +565,130 @@ impl WindowSigner {
         self.sign_dynamic_typed_data_impl(&typed_data).await
     }
 
+    /// Sign EIP-712 typed data built dynamically as JSON
+    ///
+    /// For typed data assembled at runtime (e.g. from a `HashMap` or a dapp
+    /// API response) rather than `sol!` structs. The data is validated first,
+    /// so a missing type or a message field mismatch fails with a
+    /// [`WindowError::InvalidTypedData`] message instead of an opaque wallet
+    /// error.
+    #[cfg(feature = "eip712")]
+    pub async fn sign_typed_data_json(&self, typed_data: Value) -> SignerResult<Signature> {
+        validate_typed_data(&typed_data).map_err(|e| alloy_signer::Error::other(e.to_string()))?;
+        let typed_data: TypedData = serde_json::from_value(typed_data)
+            .map_err(|e| alloy_signer::Error::other(WindowError::from(e).to_string()))?;
+        self.sign_dynamic_typed_data_impl(&typed_data).await
+    }
+
     /// Helper method to sign EIP-712 typed data
     #[cfg(feature = "eip712")]
     async fn sign_dynamic_typed_data_impl(
         &self,
         typed_data: &TypedData,
     ) -> SignerResult<Signature> {
-        // Use json_compatible() so BTreeMaps (e.g. the `types` field) are
-        // serialized as plain JS objects rather than ES6 Map instances.
-        // Wallets like MetaMask and Rabby expect a plain object structure.
-        use serde::Serialize as _;
-        let serializer = serde_wasm_bindgen::Serializer::json_compatible();
-        let typed_data_value = typed_data.serialize(&serializer).map_err(|e| {
-            alloy_signer::Error::other(format!("Failed to serialize typed data: {}", e))
-        })?;
-
-        // Create params array: [address, typedData]
-        let params_array = js_sys::Array::new();
-        params_array.push(&JsValue::from_str(&self.address.to_string()));
-        params_array.push(&typed_data_value);
-
-        let params: JsValue = params_array.into();
-
-        let promise = ethereum_request(&self.ethereum, "eth_signTypedData_v4", &params);
-        let result = JsFuture::from(promise)
-            .await
+        let json = serde_json::to_value(typed_data)
             .map_err(|e| alloy_signer::Error::other(WindowError::from(e).to_string()))?;
+        validate_typed_data(&json).map_err(|e| alloy_signer::Error::other(e.to_string()))?;
+
+        // The transport sends objects (e.g. the `types` map) as plain JS
+        // objects, which wallets like MetaMask and Rabby expect
+        let params = json!([self.address().to_string(), json]);
+
+        // Fall back to `_v3` for wallets known, or found, to lack `_v4`
+        let result = match self.cache.typed_data_v4() {
+            Some(false) => {
+                self.request_typed_data("eth_signTypedData_v3", &params)
+                    .await
+            }
+            known => match self
+                .request_typed_data("eth_signTypedData_v4", &params)
+                .await
+            {
+                Err(WindowError::MethodNotFound(_)) | Err(WindowError::UnsupportedMethod(_))
+                    if known.is_none() =>
+                {
+                    self.cache.set_typed_data_v4(false);
+                    self.request_typed_data("eth_signTypedData_v3", &params)
+                        .await
+                }
+                result => {
+                    if result.is_ok() {
+                        self.cache.set_typed_data_v4(true);
+                    }
+                    result
+                }
+            },
+        }
+        .map_err(|e| alloy_signer::Error::other(e.to_string()))?;
 
-        let sig_hex: String = serde_wasm_bindgen::from_value(result)
-            .map_err(|e| alloy_signer::Error::other(e.to_string()))?;
+        parse_signature_value(&result).map_err(|e| alloy_signer::Error::other(e.to_string()))
+    }
 
-        sig_hex
-            .parse()
-            .map_err(|e| alloy_signer::Error::other(format!("Invalid signature: {}", e)))
+    /// Send a typed data signing request
+    #[cfg(feature = "eip712")]
+    async fn request_typed_data(&self, method: &str, params: &Value) -> Result<Value> {
+        self.request(method, params.clone()).await
+    }
+
+    /// Make a request through the signer's transport
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        self.transport
+            .request_inner(method.to_string(), params)
+            .await
     }
 }
 
-#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
-#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
-impl Signer for WindowSigner {
-    async fn sign_hash(&self, _hash: &B256) -> SignerResult<Signature> {
-        Err(alloy_signer::Error::UnsupportedOperation(
-            UnsupportedSignerOperation::SignHash,
-        ))
+/// Fetch accounts via `eth_requestAccounts` or `eth_accounts`
+///
+/// Legacy providers without `eth_requestAccounts` fall back to the deprecated
+/// `ethereum.enable()`.
+async fn fetch_accounts(transport: &WindowTransport, method: &str) -> Result<Vec<Address>> {
+    if method == "eth_requestAccounts" {
+        return transport.request_accounts().await;
     }
+    parse_accounts(
+        &transport
+            .request_inner(method.to_string(), json!([]))
+            .await?,
+    )
+}
 
-    async fn sign_message(&self, message: &[u8]) -> SignerResult<Signature> {
-        let params = serde_wasm_bindgen::to_value(&json!([
-            format!("0x{}", hex::encode(message)),
-            self.address.to_string(),
-        ]))
-        .map_err(|e| alloy_signer::Error::other(e.to_string()))?;
+/// Fetch the chain ID, `None` if the wallet returns an unparseable value
+async fn fetch_chain_id(transport: &WindowTransport) -> Result<Option<u64>> {
+    let result = transport
+        .request_inner("eth_chainId".to_string(), json!([]))
+        .await?;
+    Ok(parse_chain_id(&result).ok())
+}
 
-        let promise = ethereum_request(&self.ethereum, "personal_sign", &params);
-        let result = JsFuture::from(promise)
-            .await
-            .map_err(|e| alloy_signer::Error::other(WindowError::from(e).to_string()))?;
+/// Probe EIP-5792 capabilities, empty if the wallet doesn't support them
+async fn fetch_capabilities(transport: &WindowTransport, address: Address) -> Capabilities {
+    let params = json!([address]);
+    match transport
+        .request_inner("wallet_getCapabilities".to_string(), params)
+        .await
+    {
+        Ok(result) => Capabilities::from_response(result),
+        Err(e) => {
+            tracing::debug!("wallet_getCapabilities unavailable: {}", e);
+            Capabilities::default()
+        }
+    }
+}
 
-        let sig_hex: String = serde_wasm_bindgen::from_value(result)
-            .map_err(|e| alloy_signer::Error::other(e.to_string()))?;
+// alloy declares `Signer` with `#[async_trait]`, so implementations must
+// return the boxed future it expects - native `async fn` in traits can't be
+// used here until alloy drops async_trait. Match alloy's cfg exactly so the
+// `?Send` bound lines up with the trait on every wasm target.
+#[cfg_attr(target_family = "wasm", async_trait::async_trait(?Send))]
+#[cfg_attr(not(target_family = "wasm"), async_trait::async_trait)]
+impl Signer for WindowSigner {
+    async fn sign_hash(&self, hash: &B256) -> SignerResult<Signature> {
+        self.sign_bytes(hash.as_slice(), SignMethod::EthSign).await
+    }
 
-        sig_hex
-            .parse()
-            .map_err(|e| alloy_signer::Error::other(format!("Invalid signature: {}", e)))
+    async fn sign_message(&self, message: &[u8]) -> SignerResult<Signature> {
+        self.sign_bytes(message, SignMethod::PersonalSign).await
     }
 
     #[cfg(feature = "eip712")]
@@ -223,15 +712,18 @@ impl Signer for WindowSigner {
     }
 
     fn address(&self) -> Address {
-        self.address
+        // Signers are always constructed with an active account
+        self.state().address.unwrap_or_default()
     }
 
     fn chain_id(&self) -> Option<u64> {
-        self.chain_id
+        self.state().chain_id
     }
 
     fn set_chain_id(&mut self, chain_id: Option<u64>) {
-        self.chain_id = chain_id;
+        self.transport
+            .shared_state()
+            .update(|state| state.chain_id = chain_id);
     }
 }
 
@@ -243,3 +735,65 @@ unsafe impl Send for WindowSigner {}
 
 #[cfg(target_arch = "wasm32")]
 unsafe impl Sync for WindowSigner {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockWallet;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const ADDRESS: &str = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+
+    #[wasm_bindgen_test]
+    async fn signs_through_the_transport() {
+        let signature = format!("0x{}{}1b", "11".repeat(32), "22".repeat(32));
+        let wallet = MockWallet::new(move |method, _| match method {
+            "eth_requestAccounts" => Ok(json!([ADDRESS])),
+            "eth_chainId" => Ok(json!("0x1")),
+            _ => Ok(json!(signature)),
+        });
+        let transport = wallet.transport().with_recorder(8);
+        let signer = WindowSigner::from_transport(transport).await.unwrap();
+
+        signer.sign_message(b"hi").await.unwrap();
+        assert_eq!(signer.address(), ADDRESS.parse::<Address>().unwrap());
+        assert_eq!(signer.chain_id(), Some(1));
+
+        let recorded = signer.transport().recorded_requests().unwrap();
+        let methods: Vec<_> = recorded
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["method"].clone())
+            .collect();
+        assert_eq!(
+            methods,
+            [
+                json!("eth_requestAccounts"),
+                json!("eth_chainId"),
+                json!("personal_sign")
+            ]
+        );
+    }
+
+    #[cfg(feature = "eip712")]
+    #[wasm_bindgen_test]
+    fn domain_uses_signer_chain() {
+        let wallet = MockWallet::new(|_, _| Ok(Value::Null));
+        let contract = Address::repeat_byte(0xcc);
+        let signer = WindowSigner::from_address(wallet.ethereum(), Address::ZERO, Some(137));
+
+        let domain = signer.domain("Permit2", "1", contract);
+        assert_eq!(domain.name.as_deref(), Some("Permit2"));
+        assert_eq!(domain.version.as_deref(), Some("1"));
+        assert_eq!(domain.chain_id, Some(U256::from(137)));
+        assert_eq!(domain.verifying_contract, Some(contract));
+        assert_eq!(domain.salt, None);
+
+        // Follows the wallet onto another chain
+        let mut signer = signer;
+        signer.set_chain_id(Some(10));
+        let domain = signer.domain("Permit2", "1", contract);
+        assert_eq!(domain.chain_id, Some(U256::from(10)));
+    }
+}
@@ -0,0 +1,50 @@
+//! Transaction simulation through `eth_call` before prompting the user
+
+use alloy_primitives::Bytes;
+use alloy_rpc_types_eth::{BlockId, TransactionRequest};
+
+use crate::error::{Result, WindowError};
+use crate::provider::WindowProvider;
+
+/// Outcome of [`WindowProvider::simulate`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SimulationResult {
+    /// The transaction would succeed, with the given return data
+    Success(Bytes),
+    /// The transaction would revert
+    Reverted {
+        /// Decoded revert reason (see [`WindowError::revert_reason`])
+        reason: Option<String>,
+        /// Revert message reported by the node
+        message: String,
+    },
+}
+
+impl SimulationResult {
+    /// Whether the transaction would succeed
+    pub fn is_success(&self) -> bool {
+        matches!(self, SimulationResult::Success(_))
+    }
+}
+
+impl WindowProvider {
+    /// Dry-run a transaction with `eth_call` against the latest block
+    ///
+    /// Lets dapps warn that a transaction will fail before the wallet prompts
+    /// the user. Set `from` on the request, since many calls depend on the
+    /// sender. Failures other than a revert are returned as errors.
+    pub async fn simulate(&self, tx: &TransactionRequest) -> Result<SimulationResult> {
+        match self.call_at(tx, BlockId::latest(), None).await {
+            Ok(output) => Ok(SimulationResult::Success(output)),
+            Err(e) => {
+                let reason = e.revert_reason();
+                match e {
+                    WindowError::ExecutionReverted { message, .. } => {
+                        Ok(SimulationResult::Reverted { reason, message })
+                    }
+                    e => Err(e),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Provider state shared between clones of the transport and signer
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use alloy_primitives::Address;
+use wasm_bindgen::prelude::*;
+
+use crate::accounts::accounts_from_js;
+use crate::error::WindowError;
+use crate::events::Subscription;
+use crate::normalize::chain_id_from_js;
+
+/// EIP-1193 code for a provider disconnected from all chains
+const DISCONNECTED: i64 = 4900;
+
+/// Live account and chain state of the connected wallet
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProviderState {
+    /// Active account, kept while the wallet is locked so signers retain it
+    pub address: Option<Address>,
+    /// Accounts currently exposed to the site (empty when locked or disconnected)
+    pub accounts: Vec<Address>,
+    /// Chain ID the wallet is connected to
+    pub chain_id: Option<u64>,
+    /// Reason the provider emitted a 4900 `disconnect`, `None` while connected
+    pub disconnected: Option<String>,
+}
+
+impl ProviderState {
+    /// Record a new account list, the active account first
+    pub(crate) fn set_accounts(&mut self, accounts: Vec<Address>) {
+        if let Some(address) = accounts.first() {
+            self.address = Some(*address);
+        }
+        self.accounts = accounts;
+    }
+}
+
+/// Handle to [`ProviderState`] kept current by `accountsChanged` and
+/// `chainChanged` listeners
+///
+/// Clones share the same state, so a clone created before an account or chain
+/// switch observes the switch. The listeners are removed once the last clone
+/// is dropped.
+#[derive(Clone, Debug)]
+pub(crate) struct SharedState {
+    state: Rc<RefCell<ProviderState>>,
+    _listeners: Rc<[Subscription]>,
+}
+
+impl SharedState {
+    /// Create shared state and subscribe to wallet account and chain changes
+    pub(crate) fn new(ethereum: &JsValue, initial: ProviderState) -> Self {
+        let state = Rc::new(RefCell::new(initial));
+
+        let accounts_state = state.clone();
+        let accounts_changed =
+            Subscription::new(
+                ethereum,
+                "accountsChanged",
+                move |accounts| match accounts_from_js(accounts) {
+                    Ok(accounts) => {
+                        let mut state = accounts_state.borrow_mut();
+                        state.set_accounts(accounts);
+                        // Only a connected provider reports account changes
+                        state.disconnected = None;
+                    }
+                    Err(e) => {
+                        tracing::debug!("Ignoring unparseable accountsChanged payload: {}", e)
+                    }
+                },
+            );
+
+        let chain_state = state.clone();
+        let chain_changed = Subscription::new(ethereum, "chainChanged", move |chain_id| {
+            let mut state = chain_state.borrow_mut();
+            state.chain_id = chain_id_from_js(chain_id).ok();
+            state.disconnected = None;
+        });
+
+        // EIP-1193: after a 4900 `disconnect` the provider can't serve any
+        // request until it emits `connect` again. Other codes, like 4901 for
+        // a single chain or MetaMask's recoverable 1013, don't stop requests.
+        let disconnect_state = state.clone();
+        let disconnect = Subscription::new(ethereum, "disconnect", move |error| {
+            let error = WindowError::from(error);
+            tracing::debug!("Provider disconnected: {}", error);
+            if error.code() == Some(DISCONNECTED) {
+                disconnect_state.borrow_mut().disconnected = Some(error.to_string());
+            }
+        });
+
+        let connect_state = state.clone();
+        let connect = Subscription::new(ethereum, "connect", move |_| {
+            connect_state.borrow_mut().disconnected = None;
+        });
+
+        Self {
+            state,
+            _listeners: Rc::new([accounts_changed, chain_changed, disconnect, connect]),
+        }
+    }
+
+    /// Snapshot of the current state
+    pub(crate) fn get(&self) -> ProviderState {
+        self.state.borrow().clone()
+    }
+
+    /// Modify the state in place
+    pub(crate) fn update(&self, f: impl FnOnce(&mut ProviderState)) {
+        f(&mut self.state.borrow_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockWallet;
+    use serde_json::{json, Value};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn disconnect(code: i64) -> Value {
+        json!({ "code": code, "message": "disconnected" })
+    }
+
+    #[wasm_bindgen_test]
+    fn latches_full_disconnects_until_reconnect() {
+        let wallet = MockWallet::new(|_, _| Ok(Value::Null));
+        let state = SharedState::new(&wallet.ethereum(), ProviderState::default());
+
+        wallet.emit("disconnect", disconnect(4900));
+        assert!(state.get().disconnected.is_some());
+        wallet.emit("connect", json!({ "chainId": "0x1" }));
+        assert_eq!(state.get().disconnected, None);
+    }
+
+    #[wasm_bindgen_test]
+    fn ignores_recoverable_disconnects() {
+        let wallet = MockWallet::new(|_, _| Ok(Value::Null));
+        let state = SharedState::new(&wallet.ethereum(), ProviderState::default());
+
+        wallet.emit("disconnect", disconnect(4901));
+        wallet.emit("disconnect", disconnect(1013));
+        assert_eq!(state.get().disconnected, None);
+    }
+
+    #[wasm_bindgen_test]
+    fn clears_disconnect_on_wallet_events() {
+        let wallet = MockWallet::new(|_, _| Ok(Value::Null));
+        let state = SharedState::new(&wallet.ethereum(), ProviderState::default());
+
+        wallet.emit("disconnect", disconnect(4900));
+        wallet.emit("chainChanged", json!("0x89"));
+        assert_eq!(state.get().disconnected, None);
+        assert_eq!(state.get().chain_id, Some(137));
+
+        wallet.emit("disconnect", disconnect(4900));
+        wallet.emit(
+            "accountsChanged",
+            json!(["0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"]),
+        );
+        assert_eq!(state.get().disconnected, None);
+    }
+}
@@ -1,40 +1,90 @@
 //! WindowTransport implementation - routes Alloy RPC calls through window.ethereum
 
-use alloy_json_rpc::{RequestPacket, ResponsePacket};
-use alloy_transport::{TransportError, TransportFut};
-use serde_json::Value;
+use alloy_json_rpc::{Id, RequestPacket, ResponsePacket};
+use alloy_primitives::Address;
+use alloy_rpc_types_eth::BlockId;
+use alloy_transport::{BoxTransport, TransportError, TransportFut};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::task::{Context, Poll};
 use tower::Service;
+use tracing::Instrument;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 
+use crate::accounts::{accounts_from_js, parse_accounts};
+use crate::bigint::{self, BigIntParams};
 use crate::error::{Result, WindowError};
+use crate::events::{ProviderMessage, Subscription};
+use crate::js_bindings::{
+    ethereum_request, ethereum_request_with_options, ethereum_rpc_url, ethereum_version,
+    get_ethereum, legacy_enable,
+};
+use crate::limit::ConcurrencyLimit;
+use crate::logging::{log_request_failure, RequestContext};
+use crate::normalize::{
+    apply_default_block, chain_id_from_js, check_depth, check_transaction_hash, insert_chain_id,
+    insert_from, lacks_from, normalize_address_case, normalize_params, normalize_result,
+    unwrap_envelope, AddressCase,
+};
+use crate::pending::PendingRequests;
+use crate::queue::RequestQueue;
+use crate::recorder::Recorder;
+use crate::response::{error_response, success_response};
+use crate::state::{ProviderState, SharedState};
 
-/// Get window.ethereum object
-#[wasm_bindgen(inline_js = r#"
-export function get_ethereum() {
-    if (typeof window !== 'undefined' && window.ethereum) {
-        return window.ethereum;
-    }
-    return null;
-}
+/// Params nesting depth allowed unless configured otherwise
+///
+/// Far beyond any real request, while keeping the recursive conversion well
+/// within the small WASM stack.
+const DEFAULT_MAX_PARAM_DEPTH: usize = 128;
 
-export function ethereum_request(ethereum, method, params) {
-    return ethereum.request({ method, params });
-}
-"#)]
-extern "C" {
-    #[wasm_bindgen(js_name = get_ethereum)]
-    fn get_ethereum() -> JsValue;
-
-    #[wasm_bindgen(js_name = ethereum_request)]
-    fn ethereum_request(ethereum: &JsValue, method: &str, params: &JsValue) -> js_sys::Promise;
+/// Optional behaviour configured through the `with_*` builder methods
+#[derive(Clone, Debug, Default)]
+struct TransportOptions {
+    /// Chain every `eth_sendTransaction` must be sent on
+    pinned_chain: Option<u64>,
+    /// Maximum number of batch requests in flight at once
+    max_batch_size: Option<usize>,
+    /// Issue batch requests one at a time
+    batch_disabled: bool,
+    /// Queue every request goes through when single-flight mode is enabled
+    single_flight: Option<RequestQueue>,
+    /// Cap on requests in flight at once, unlimited if `None`
+    max_concurrency: Option<ConcurrencyLimit>,
+    /// Mask addresses when logging failed requests
+    redact_logs: bool,
+    /// Casing applied to addresses in outgoing params, untouched if `None`
+    address_case: Option<AddressCase>,
+    /// Correlation fields recorded with every request
+    context: RequestContext,
+    /// Block used by read methods whose block parameter was omitted
+    default_block: Option<BlockId>,
+    /// Log of recent requests when recording is enabled
+    recorder: Option<Recorder>,
+    /// Param fields sent as JS BigInts, per method
+    bigint_params: BigIntParams,
+    /// Sender set on `eth_sendTransaction` requests without `from`
+    default_from: Option<Address>,
+    /// Add `chainId` to transaction objects that lack it
+    tx_chain_id: bool,
+    /// Maximum nesting depth of params, [`DEFAULT_MAX_PARAM_DEPTH`] if `None`
+    max_param_depth: Option<usize>,
+    /// Replaces request IDs in responses, for deterministic snapshots
+    #[cfg(feature = "testing")]
+    id_override: Option<fn(&Id) -> Id>,
 }
 
 /// Transport that uses window.ethereum (EIP-1193)
 #[derive(Clone, Debug)]
 pub struct WindowTransport {
     ethereum: JsValue,
+    /// Whether `ethereum` was window.ethereum when the transport was created
+    global: bool,
+    state: SharedState,
+    options: TransportOptions,
+    pending: PendingRequests,
 }
 
 impl WindowTransport {
@@ -46,73 +96,681 @@ impl WindowTransport {
             return Err(WindowError::NoWallet);
         }
 
-        Ok(Self { ethereum })
-    }
-
-    /// Make a single RPC request
-    async fn request_inner(&self, method: String, params: Value) -> Result<Value> {
-        // For eth_call, transform "input" to "data" since window.ethereum expects "data"
-        let params = if method == "eth_call" {
-            tracing::debug!("Original params: {:?}", params);
-            match params {
-                Value::Array(mut arr) if arr.len() > 0 => {
-                    // Transform the first element (the transaction object)
-                    if let Some(Value::Object(obj)) = arr.get(0) {
-                        if obj.contains_key("input") {
-                            tracing::debug!("Found 'input', transforming to 'data'");
-                            // Rebuild the object with "data" instead of "input"
-                            let mut new_obj = serde_json::Map::new();
-                            for (k, v) in obj {
-                                if k == "input" {
-                                    new_obj.insert("data".to_string(), v.clone());
-                                } else {
-                                    new_obj.insert(k.clone(), v.clone());
-                                }
-                            }
-                            tracing::debug!("New object: {:?}", new_obj);
-                            arr[0] = Value::Object(new_obj);
-                        }
+        let state = SharedState::new(&ethereum, ProviderState::default());
+        Ok(Self::with_state(ethereum, state))
+    }
+
+    /// Create a transport on top of existing shared state
+    pub(crate) fn with_state(ethereum: JsValue, state: SharedState) -> Self {
+        Self {
+            global: js_sys::Object::is(&ethereum, &get_ethereum()),
+            ethereum,
+            state,
+            options: TransportOptions::default(),
+            pending: PendingRequests::default(),
+        }
+    }
+
+    /// Pin transactions to a chain
+    ///
+    /// Before every `eth_sendTransaction`, the transport re-reads `eth_chainId`
+    /// and aborts with [`WindowError::ChainMismatch`] if the wallet is no longer
+    /// on `expected`. This protects against the wallet silently switching
+    /// networks between the dapp's own chain check and the send.
+    ///
+    /// # Note
+    /// This narrows the race but can't close it: the wallet can still switch
+    /// chains after the check and before it processes the transaction, since
+    /// EIP-1193 offers no way to bind a request to a chain.
+    pub fn with_chain_pinning(mut self, expected: u64) -> Self {
+        self.options.pinned_chain = Some(expected);
+        self
+    }
+
+    /// Limit how many requests of a batch are in flight at once
+    ///
+    /// EIP-1193 has no batch call, so every request of an alloy batch goes
+    /// through its own `ethereum.request`; this caps their concurrency. Larger
+    /// batches are split into chunks of `max` requests; chunks run one after
+    /// another and the responses are stitched back into a single batch.
+    pub fn with_max_batch_size(mut self, max: usize) -> Self {
+        self.options.max_batch_size = Some(max.max(1));
+        self
+    }
+
+    /// Decompose batches into sequential single requests
+    ///
+    /// For wallets that can't handle concurrent requests at all; equivalent
+    /// to a [`with_max_batch_size`](Self::with_max_batch_size) of 1.
+    pub fn with_batch_disabled(mut self) -> Self {
+        self.options.batch_disabled = true;
+        self
+    }
+
+    /// Rewrite addresses in all outgoing params to the given casing
+    ///
+    /// Every `0x`-prefixed 40-hex-digit string in the params is normalized.
+    /// By default addresses are sent as alloy serializes them; use this for
+    /// wallets (e.g. some hardware-wallet bridges) that mis-validate
+    /// checksummed addresses.
+    pub fn with_address_case(mut self, case: AddressCase) -> Self {
+        self.options.address_case = Some(case);
+        self
+    }
+
+    /// Use `block` for read calls that don't specify one
+    ///
+    /// Applies to methods taking a block parameter (`eth_call`,
+    /// `eth_getBalance`, `eth_getStorageAt`, ...) when it is omitted or
+    /// `null`, e.g. to read at `safe` or `finalized` and avoid reorg-sensitive
+    /// results. An explicitly provided block is never replaced.
+    ///
+    /// # Note
+    /// Alloy's `get_balance`, `get_code_at` and similar always send a block,
+    /// `latest` unless set with `.block_id(..)`, so they aren't affected.
+    /// `call` and `estimate_gas` omit it unless one is set.
+    pub fn with_default_block(mut self, block: BlockId) -> Self {
+        self.options.default_block = Some(block);
+        self
+    }
+
+    /// Mask addresses in the params logged for failed requests
+    ///
+    /// Failed requests are logged at `warn` level with their method, params
+    /// and error. With redaction, addresses in the params are shortened to
+    /// their first and last four hex digits.
+    pub fn with_redacted_logs(mut self) -> Self {
+        self.options.redact_logs = true;
+        self
+    }
+
+    /// Attach a correlation field, such as a trace ID, to every request
+    ///
+    /// EIP-1193 has no equivalent of HTTP headers, so nothing reaches the
+    /// wallet: the fields are recorded on the `wallet_request` tracing span
+    /// and in failure logs, to tell which UI action triggered which wallet
+    /// call. Call it on a clone to tag a single action's requests; the clone
+    /// still shares the wallet state with the original.
+    pub fn with_context(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.context = self.options.context.with(key.into(), value.into());
+        self
+    }
+
+    /// Record the most recent `capacity` requests for debugging
+    ///
+    /// Each request is kept with the params sent to the wallet and its result
+    /// or error, and can be exported with
+    /// [`recorded_requests`](Self::recorded_requests), e.g. to attach to a
+    /// bug report and replay against a mock provider. Older requests are
+    /// dropped once `capacity` is reached. The log is shared by clones.
+    ///
+    /// # Note
+    /// The log holds addresses and other request data in plain text;
+    /// [`with_redacted_logs`](Self::with_redacted_logs) doesn't apply to it.
+    pub fn with_recorder(mut self, capacity: usize) -> Self {
+        self.options.recorder = Some(Recorder::new(capacity));
+        self
+    }
+
+    /// Recorded requests as a JSON array, oldest first
+    ///
+    /// Entries are `{ method, params, result }` or
+    /// `{ method, params, error: { code, message } }`. Returns `None` unless
+    /// recording was enabled with [`with_recorder`](Self::with_recorder).
+    pub fn recorded_requests(&self) -> Option<Value> {
+        self.options.recorder.as_ref().map(Recorder::to_json)
+    }
+
+    /// Send the given param fields of `method` as JS BigInts
+    ///
+    /// For wallets implementing newer specs (e.g. EIP-5792 `wallet_sendCalls`)
+    /// that validate param types strictly and expect BigInts where other
+    /// methods take hex strings. Paths are dot-separated object keys and
+    /// array indices, with `*` matching any key or index:
+    ///
+    /// ```rust,ignore
+    /// let transport = WindowTransport::new()?
+    ///     .with_bigint_params("wallet_sendCalls", &["0.calls.*.value"]);
+    /// ```
+    ///
+    /// Matched hex quantities and integers become BigInts; other values and
+    /// all other methods are sent unchanged.
+    pub fn with_bigint_params(mut self, method: impl Into<String>, paths: &[&str]) -> Self {
+        self.options.bigint_params = self.options.bigint_params.with(method.into(), paths);
+        self
+    }
+
+    /// Send `eth_sendTransaction` requests without `from` from `address`
+    ///
+    /// `eth_sendTransaction` requires `from`: some wallets fill in the active
+    /// account when it's missing, others reject the transaction. With a
+    /// default set, a missing `from` is filled in after checking `address` is
+    /// still among the accounts exposed to the site (`eth_accounts`), failing
+    /// with [`WindowError::AccountMismatch`] (or [`WindowError::NoAccounts`])
+    /// otherwise. An explicit `from` is sent unchanged.
+    pub fn with_default_from(mut self, address: Address) -> Self {
+        self.options.default_from = Some(address);
+        self
+    }
+
+    /// Include the wallet's `chainId` in `eth_sendTransaction` and
+    /// `eth_estimateGas` transaction objects
+    ///
+    /// Some wallet versions reject transactions without `chainId`, even though
+    /// it's implied by the active chain. When enabled, a missing `chainId` is
+    /// filled in (as a hex quantity) from the tracked chain, asking the wallet
+    /// if it isn't known yet; an explicit `chainId` is left untouched. Off by
+    /// default.
+    pub fn include_chain_id_in_tx(mut self, include: bool) -> Self {
+        self.options.tx_chain_id = include;
+        self
+    }
+
+    /// Limit how deeply params may be nested
+    ///
+    /// Normalizing params and converting them to JS recurse once per level,
+    /// so a pathologically deep structure (a bug, or untrusted input passed
+    /// through) could overflow the WASM stack. Deeper params, or request
+    /// options, fail with [`WindowError::DepthLimitExceeded`] before any of
+    /// that runs. Defaults to 128.
+    pub fn with_max_param_depth(mut self, depth: usize) -> Self {
+        self.options.max_param_depth = Some(depth);
+        self
+    }
+
+    /// Allow at most `max` requests in flight at once, queuing the rest
+    ///
+    /// Unlike batching, this throttles requests issued independently (by
+    /// different components, or by clones of this transport). Slow injected
+    /// providers time out or rate-limit under a burst of concurrent requests;
+    /// capping concurrency smooths the burst out. Signing requests of a
+    /// [`WindowSigner`](crate::WindowSigner) created with
+    /// [`from_transport`](crate::WindowSigner::from_transport) count against
+    /// the limit too. Unlimited by default; a limit of 0 is treated as 1.
+    pub fn with_max_concurrency(mut self, max: usize) -> Self {
+        self.options.max_concurrency = Some(ConcurrencyLimit::new(max));
+        self
+    }
+
+    /// Serialize all requests through a FIFO queue
+    ///
+    /// Each request waits for every earlier request (from this transport or
+    /// its clones) to complete before it is sent, so the wallet sees them in
+    /// issue order. Use this if a wallet shows prompts out of order.
+    ///
+    /// # Note
+    /// This gives up all concurrency: batches and reads issued while a prompt
+    /// is open wait until the user responds, so latency adds up. The default
+    /// concurrent behaviour is faster and fine for most wallets.
+    pub fn single_flight(mut self) -> Self {
+        self.options.single_flight = Some(RequestQueue::default());
+        self
+    }
+
+    /// Call `callback` with the new pending request count whenever it changes
+    ///
+    /// Useful to drive a global loading indicator from a shared transport.
+    /// The count is shared by all clones of this transport.
+    ///
+    /// # Note
+    /// Changes caused by requests issued from within the callback don't
+    /// invoke it again.
+    pub fn with_pending_callback(self, callback: impl FnMut(usize) + 'static) -> Self {
+        self.pending.set_callback(callback);
+        self
+    }
+
+    /// Replace the ID of every response with `f(request_id)`
+    ///
+    /// Alloy assigns increasing request IDs, so responses differ between runs.
+    /// Forcing fixed IDs lets tests compare exact response JSON.
+    #[cfg(feature = "testing")]
+    pub fn with_id_override(mut self, f: fn(&Id) -> Id) -> Self {
+        self.options.id_override = Some(f);
+        self
+    }
+
+    /// Erase the transport type behind Alloy's [`BoxTransport`]
+    ///
+    /// Useful for storing the transport (or a provider built on it) in app-wide
+    /// state without naming the concrete type. Requests behave exactly as they
+    /// do through the unboxed transport.
+    pub fn boxed(self) -> BoxTransport {
+        BoxTransport::new(self)
+    }
+
+    /// Whether window.ethereum no longer refers to the provider this transport uses
+    ///
+    /// Some wallet extensions replace window.ethereum after page load, e.g.
+    /// when the user picks another wallet, leaving the transport talking to
+    /// the previous one. Rebuild the transport or call
+    /// [`refresh`](Self::refresh) when this returns `true`. Always `false` for
+    /// transports of a provider that wasn't window.ethereum, such as one found
+    /// by EIP-6963 discovery.
+    pub fn is_stale(&self) -> bool {
+        self.global && !js_sys::Object::is(&self.ethereum, &get_ethereum())
+    }
+
+    /// Re-capture window.ethereum, keeping the configured options
+    ///
+    /// The account and chain state is reset and tracked on the new provider.
+    ///
+    /// # Note
+    /// Only this handle is updated; existing clones keep the previous provider.
+    pub fn refresh(&mut self) -> Result<()> {
+        let ethereum = get_ethereum();
+        if ethereum.is_null() || ethereum.is_undefined() {
+            return Err(WindowError::NoWallet);
+        }
+
+        self.state = SharedState::new(&ethereum, ProviderState::default());
+        self.ethereum = ethereum;
+        self.global = true;
+        Ok(())
+    }
+
+    /// Get the live account and chain state, shared by all clones of this transport
+    ///
+    /// The state is updated by wallet events and by `eth_accounts`,
+    /// `eth_requestAccounts` and `eth_chainId` responses.
+    pub fn state(&self) -> ProviderState {
+        self.state.get()
+    }
+
+    /// Handle to the live state, for signers sharing this transport
+    pub(crate) fn shared_state(&self) -> &SharedState {
+        &self.state
+    }
+
+    /// Number of requests sent through this transport or its clones that
+    /// haven't completed yet, including those waiting in single-flight mode
+    pub fn pending_requests(&self) -> usize {
+        self.pending.get()
+    }
+
+    /// RPC endpoint the wallet uses for the current chain, if it exposes one
+    ///
+    /// Best effort: no standard exposes this, so known non-standard provider
+    /// properties are probed and only `http(s)` URLs are returned. When
+    /// available, reads can go straight to the wallet's node over HTTP
+    /// instead of through the slower injected provider.
+    ///
+    /// # Note
+    ///
+    /// The URL may change when the user switches chains; query it again on
+    /// `chainChanged`.
+    pub fn active_rpc_url(&self) -> Option<String> {
+        ethereum_rpc_url(&self.ethereum)
+    }
+
+    /// Version the provider reports for itself, if it exposes one
+    ///
+    /// Best effort: EIP-1193 defines no version, so known non-standard
+    /// properties (`version`, `walletVersion`, ...) are probed. Useful in bug
+    /// reports, and for working around known-buggy wallet releases. The format
+    /// is wallet-specific.
+    pub fn provider_version(&self) -> Option<String> {
+        ethereum_version(&self.ethereum)
+    }
+
+    /// Listen for EIP-1193 `message` events
+    ///
+    /// Wallets deliver `eth_subscribe` notifications and wallet-specific
+    /// notifications through this event. The listener is removed when the
+    /// returned [`Subscription`] is dropped.
+    pub fn on_message(&self, mut callback: impl FnMut(ProviderMessage) + 'static) -> Subscription {
+        Subscription::new(&self.ethereum, "message", move |message| {
+            match serde_wasm_bindgen::from_value(message) {
+                Ok(message) => callback(ProviderMessage::from_value(message)),
+                Err(e) => tracing::debug!("Ignoring unparseable message event: {}", e),
+            }
+        })
+    }
+
+    /// Listen for the given events with their raw payloads, for debugging
+    ///
+    /// `callback` receives the event name and payload untouched, including
+    /// non-standard events of a particular wallet that the typed listeners
+    /// don't model. Pass the standard names (`connect`, `disconnect`,
+    /// `chainChanged`, `accountsChanged`, `message`) alongside any
+    /// wallet-specific ones. All listeners are removed when the returned
+    /// subscriptions are dropped.
+    pub fn on_any_event(
+        &self,
+        events: &[&str],
+        callback: impl FnMut(&str, JsValue) + 'static,
+    ) -> Vec<Subscription> {
+        let callback = Rc::new(RefCell::new(callback));
+        events
+            .iter()
+            .map(|&event| {
+                let callback = callback.clone();
+                let name = event.to_string();
+                Subscription::new(&self.ethereum, event, move |payload| {
+                    // A wallet emitting from inside the callback can't re-enter it
+                    if let Ok(mut callback) = callback.try_borrow_mut() {
+                        callback(&name, payload);
                     }
-                    tracing::debug!("Transformed params: {:?}", arr);
-                    Value::Array(arr)
+                })
+            })
+            .collect()
+    }
+
+    /// Listen for `chainChanged` events with the new chain ID
+    pub(crate) fn on_chain_changed(&self, mut callback: impl FnMut(u64) + 'static) -> Subscription {
+        Subscription::new(
+            &self.ethereum,
+            "chainChanged",
+            move |chain_id| match chain_id_from_js(chain_id) {
+                Ok(chain_id) => callback(chain_id),
+                Err(e) => tracing::debug!("Ignoring unparseable chainChanged payload: {}", e),
+            },
+        )
+    }
+
+    /// Make a request, passing wallet-specific `options` to `ethereum.request`
+    ///
+    /// The request is sent as `ethereum.request({ method, params }, options)`,
+    /// going through the same normalization, queueing and error handling as
+    /// any other request. For wallets that accept per-request metadata.
+    ///
+    /// # Note
+    ///
+    /// Support is wallet-dependent: most wallets ignore the extra argument,
+    /// and it's dropped for providers without `request()`.
+    pub async fn request_with_options(
+        &self,
+        method: impl Into<String>,
+        params: Value,
+        options: Value,
+    ) -> Result<Value> {
+        self.request_queued(method.into(), params, Some(&options))
+            .await
+    }
+
+    /// Make a single RPC request, waiting for its turn in single-flight mode
+    pub(crate) async fn request_inner(&self, method: String, params: Value) -> Result<Value> {
+        self.request_queued(method, params, None).await
+    }
+
+    /// Request accounts via `eth_requestAccounts`
+    ///
+    /// Legacy providers without `eth_requestAccounts` fall back to the
+    /// deprecated `ethereum.enable()`; the original error is kept if that's
+    /// missing too.
+    pub(crate) async fn request_accounts(&self) -> Result<Vec<Address>> {
+        let result = self
+            .request_inner("eth_requestAccounts".to_string(), json!([]))
+            .await;
+        match result {
+            Err(e @ WindowError::MethodNotFound(_))
+            | Err(e @ WindowError::UnsupportedMethod(_)) => {
+                match legacy_enable(&self.ethereum).await {
+                    Some(result) => accounts_from_js(result?),
+                    None => Err(e),
                 }
-                _ => params,
             }
-        } else {
-            params
+            result => parse_accounts(&result?),
+        }
+    }
+
+    /// Make a single RPC request with optional request options
+    async fn request_queued(
+        &self,
+        method: String,
+        params: Value,
+        options: Option<&Value>,
+    ) -> Result<Value> {
+        let _pending = self.pending.start();
+        self.ensure_connected()?;
+        // Before normalization, which recurses into params
+        let max_depth = self
+            .options
+            .max_param_depth
+            .unwrap_or(DEFAULT_MAX_PARAM_DEPTH);
+        check_depth(&params, max_depth)?;
+        if let Some(options) = options {
+            check_depth(options, max_depth)?;
+        }
+        let _permit = match &self.options.max_concurrency {
+            Some(limit) => Some(limit.acquire().await),
+            None => None,
+        };
+        let _ticket = match &self.options.single_flight {
+            Some(queue) => Some(queue.acquire().await),
+            None => None,
         };
+        self.send_request(method, params, options).await
+    }
 
+    /// Send a single RPC request to the wallet
+    async fn send_request(
+        &self,
+        method: String,
+        params: Value,
+        options: Option<&Value>,
+    ) -> Result<Value> {
+        if method == "eth_sendTransaction" {
+            self.verify_pinned_chain().await?;
+        }
+
+        let params = self.add_tx_from(&method, params).await?;
+        let mut params = self
+            .add_tx_chain_id(&method, normalize_params(&method, params))
+            .await?;
+        if let Some(block) = self.options.default_block {
+            params = apply_default_block(&method, params, block);
+        }
+        if let Some(case) = self.options.address_case {
+            params = normalize_address_case(params, case);
+        }
+        let context = &self.options.context;
+        let span = tracing::debug_span!("wallet_request", method = %method, context = %context);
+        let result = self
+            .dispatch(&method, &params, options)
+            .instrument(span)
+            .await;
+        if let Err(e) = &result {
+            log_request_failure(&method, &params, e, context, self.options.redact_logs);
+        }
+        if let Some(recorder) = &self.options.recorder {
+            recorder.record(&method, &params, &result);
+        }
+        result
+    }
+
+    /// Issue a normalized request to window.ethereum and convert the result
+    async fn dispatch(
+        &self,
+        method: &str,
+        params: &Value,
+        options: Option<&Value>,
+    ) -> Result<Value> {
         // Convert serde_json::Value to JsValue manually using js_sys
         // This avoids serde_wasm_bindgen serialization issues with Map types
         // MetaMask requires params to be an array or object, not null
-        let params_js = match &params {
+        let params_js = match params {
             Value::Null => {
                 // Convert null to empty array for MetaMask compatibility
                 let arr = js_sys::Array::new();
                 arr.into()
             }
-            _ => self.json_to_js(&params)?,
+            _ => self.json_to_js(
+                params,
+                &mut Vec::new(),
+                self.options.bigint_params.paths(method),
+            )?,
         };
 
-        // Log the JS value
-        let params_str = js_sys::JSON::stringify(&params_js)
-            .map(|s| s.as_string().unwrap_or_default())
-            .unwrap_or_default();
-        tracing::debug!("params_js as JSON: {}", params_str);
-
         // Make the request
-        let promise = ethereum_request(&self.ethereum, &method, &params_js);
+        let promise = match options {
+            Some(options) => {
+                let options = self.json_to_js(options, &mut Vec::new(), &[])?;
+                ethereum_request_with_options(&self.ethereum, method, &params_js, &options)
+            }
+            None => ethereum_request(&self.ethereum, method, &params_js),
+        };
         let result = JsFuture::from(promise).await?;
 
-        tracing::debug!("Result: {:?}", result);
+        self.observe_state(method, &result);
+
+        // Lookups like eth_getTransactionByHash legitimately resolve to null
+        // (or undefined in some wallets) when nothing is found
+        let result = if result.is_null() || result.is_undefined() {
+            Value::Null
+        } else {
+            // Convert back to serde_json::Value, unwrapping raw JSON-RPC responses
+            unwrap_envelope(serde_wasm_bindgen::from_value(result)?)?
+        };
+        let result = normalize_result(method, result);
+
+        check_transaction_hash(method, result)
+    }
 
-        // Convert back to serde_json::Value
-        Ok(serde_wasm_bindgen::from_value(result)?)
+    /// ID to put in the response to a request with ID `id`
+    fn response_id(&self, id: &Id) -> Id {
+        #[cfg(feature = "testing")]
+        if let Some(f) = self.options.id_override {
+            return f(id);
+        }
+        id.clone()
+    }
+
+    /// Fail fast while the provider is disconnected
+    fn ensure_connected(&self) -> Result<()> {
+        match self.state.get().disconnected {
+            Some(reason) => Err(WindowError::Disconnected(reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the wallet is still on the pinned chain, if any
+    async fn verify_pinned_chain(&self) -> Result<()> {
+        let Some(expected) = self.options.pinned_chain else {
+            return Ok(());
+        };
+
+        let actual = self.fetch_chain_id().await?;
+        if actual != expected {
+            return Err(WindowError::ChainMismatch { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Set the default sender on an `eth_sendTransaction` without `from`
+    async fn add_tx_from(&self, method: &str, params: Value) -> Result<Value> {
+        let Some(from) = self.options.default_from else {
+            return Ok(params);
+        };
+        if method != "eth_sendTransaction" || !lacks_from(&params) {
+            return Ok(params);
+        }
+
+        // Only send from an account the user exposed to the site
+        let promise = ethereum_request(&self.ethereum, "eth_accounts", &js_sys::Array::new());
+        let accounts = accounts_from_js(JsFuture::from(promise).await?)?;
+        if !accounts.contains(&from) {
+            let actual = *accounts.first().ok_or(WindowError::NoAccounts)?;
+            return Err(WindowError::AccountMismatch {
+                expected: from,
+                actual,
+            });
+        }
+
+        Ok(insert_from(params, from))
+    }
+
+    /// Add the wallet's chain ID to the transaction object if enabled and absent
+    async fn add_tx_chain_id(&self, method: &str, params: Value) -> Result<Value> {
+        if !self.options.tx_chain_id
+            || !matches!(method, "eth_sendTransaction" | "eth_estimateGas")
+            || params.get(0).is_some_and(|tx| tx.get("chainId").is_some())
+        {
+            return Ok(params);
+        }
+
+        let chain_id = match self.state.get().chain_id {
+            Some(chain_id) => chain_id,
+            None => self.fetch_chain_id().await?,
+        };
+        Ok(insert_chain_id(params, chain_id))
+    }
+
+    /// Ask the wallet for its current chain, bypassing the cached state
+    async fn fetch_chain_id(&self) -> Result<u64> {
+        let promise = ethereum_request(&self.ethereum, "eth_chainId", &js_sys::Array::new());
+        chain_id_from_js(JsFuture::from(promise).await?)
+    }
+
+    /// Update the shared state from responses that reveal accounts or chain
+    fn observe_state(&self, method: &str, result: &JsValue) {
+        match method {
+            "eth_accounts" | "eth_requestAccounts" => {
+                if let Ok(accounts) = accounts_from_js(result.clone()) {
+                    self.state.update(|state| state.set_accounts(accounts));
+                }
+            }
+            "eth_chainId" => {
+                if let Ok(chain_id) = chain_id_from_js(result.clone()) {
+                    self.state.update(|state| state.chain_id = Some(chain_id));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Make several RPC requests concurrently
+    ///
+    /// Every `ethereum.request` call of a chunk is issued before any is
+    /// awaited (the equivalent of `Promise.all`), so a chunk costs a single
+    /// wallet round-trip instead of one per request. Chunks are bounded by
+    /// [`with_max_batch_size`](Self::with_max_batch_size), and requests run one
+    /// at a time when batching is disabled. Results are returned in order.
+    pub(crate) async fn request_all(&self, requests: Vec<(String, Value)>) -> Vec<Result<Value>> {
+        let mut results = Vec::with_capacity(requests.len());
+        let mut pending = requests.into_iter().peekable();
+
+        while pending.peek().is_some() {
+            if self.options.batch_disabled {
+                if let Some((method, params)) = pending.next() {
+                    results.push(self.request_inner(method, params).await);
+                }
+                continue;
+            }
+
+            let chunk_size = self.options.max_batch_size.unwrap_or(usize::MAX);
+            let chunk: Vec<_> = pending.by_ref().take(chunk_size).collect();
+            let chunk_results = futures::future::join_all(
+                chunk
+                    .iter()
+                    .map(|(method, params)| self.request_inner(method.clone(), params.clone())),
+            )
+            .await;
+
+            results.extend(chunk_results);
+        }
+
+        results
     }
 
     /// Convert serde_json::Value to JsValue manually
     /// This is needed because serde_wasm_bindgen has issues with Map serialization
-    fn json_to_js(&self, value: &Value) -> Result<JsValue> {
+    ///
+    /// `path` locates `value` in the params; values at one of `bigints` are
+    /// encoded as BigInts. The depth limit has already been checked by
+    /// `request_queued`, bounding the recursion.
+    fn json_to_js(
+        &self,
+        value: &Value,
+        path: &mut Vec<String>,
+        bigints: &[Vec<String>],
+    ) -> Result<JsValue> {
+        if !bigints.is_empty() && bigint::matches(bigints, path) {
+            if let Some(bigint) = bigint::to_bigint(value) {
+                return Ok(bigint);
+            }
+        }
+
         match value {
             Value::Null => Ok(JsValue::NULL),
             Value::Bool(b) => Ok(JsValue::from(*b)),
@@ -130,15 +788,21 @@ impl WindowTransport {
             Value::String(s) => Ok(JsValue::from_str(s)),
             Value::Array(arr) => {
                 let js_array = js_sys::Array::new();
-                for item in arr {
-                    js_array.push(&self.json_to_js(item)?);
+                for (i, item) in arr.iter().enumerate() {
+                    path.push(i.to_string());
+                    let js_val = self.json_to_js(item, path, bigints);
+                    path.pop();
+                    js_array.push(&js_val?);
                 }
                 Ok(js_array.into())
             }
             Value::Object(obj) => {
                 let js_object = js_sys::Object::new();
                 for (key, val) in obj {
-                    let js_val = self.json_to_js(val)?;
+                    path.push(key.clone());
+                    let js_val = self.json_to_js(val, path, bigints);
+                    path.pop();
+                    let js_val = js_val?;
                     js_sys::Reflect::set(&js_object, &JsValue::from_str(key), &js_val)
                         .map_err(|_| WindowError::SerializationError)?;
                 }
@@ -154,13 +818,15 @@ impl Service<RequestPacket> for WindowTransport {
     type Future = TransportFut<'static>;
 
     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
-        // Always ready since we're using window.ethereum
-        Poll::Ready(Ok(()))
+        // window.ethereum accepts requests at any time unless it disconnected
+        Poll::Ready(
+            self.ensure_connected()
+                .map_err(|e| TransportError::local_usage_str(&e.to_string())),
+        )
     }
 
     fn call(&mut self, req: RequestPacket) -> Self::Future {
-        let ethereum = self.ethereum.clone();
-        let transport = Self { ethereum };
+        let transport = self.clone();
 
         Box::pin(async move {
             match req {
@@ -169,66 +835,48 @@ impl Service<RequestPacket> for WindowTransport {
 
                     // Parse params from RawValue to Value
                     let params = match single.params() {
-                        Some(raw) => serde_json::from_str(raw.get())
-                            .map_err(|e| TransportError::local_usage(e))?,
+                        Some(raw) => {
+                            serde_json::from_str(raw.get()).map_err(TransportError::local_usage)?
+                        }
                         None => Value::Null,
                     };
 
                     match transport.request_inner(method, params).await {
-                        Ok(result) => {
-                            // Build successful response
-                            let response = serde_json::json!({
-                                "jsonrpc": "2.0",
-                                "id": single.id(),
-                                "result": result,
-                            });
-                            let response_packet = serde_json::from_value(response)
-                                .map_err(|e| TransportError::local_usage(e))?;
-                            Ok(ResponsePacket::Single(response_packet))
-                        }
-                        Err(e) => Err(TransportError::local_usage_str(&e.to_string())),
+                        // Build successful response
+                        Ok(result) => Ok(ResponsePacket::Single(success_response(
+                            transport.response_id(single.id()),
+                            &result,
+                        )?)),
+                        Err(e) => Err(e.into()),
                     }
                 }
                 RequestPacket::Batch(batch) => {
-                    // Process each request in the batch
-                    let mut responses = Vec::new();
-
+                    // Parse params from RawValue to Value for every request
+                    let mut requests = Vec::with_capacity(batch.len());
                     for single in batch.iter() {
-                        let method = single.method().to_string();
-
-                        // Parse params from RawValue to Value
                         let params = match single.params() {
                             Some(raw) => serde_json::from_str(raw.get())
-                                .map_err(|e| TransportError::local_usage(e))?,
+                                .map_err(TransportError::local_usage)?,
                             None => Value::Null,
                         };
+                        requests.push((single.method().to_string(), params));
+                    }
 
-                        match transport.request_inner(method, params).await {
-                            Ok(result) => {
-                                let response = serde_json::json!({
-                                    "jsonrpc": "2.0",
-                                    "id": single.id(),
-                                    "result": result,
-                                });
-                                responses.push(response);
-                            }
-                            Err(e) => {
-                                let error_response = serde_json::json!({
-                                    "jsonrpc": "2.0",
-                                    "id": single.id(),
-                                    "error": {
-                                        "code": -32000,
-                                        "message": e.to_string(),
-                                    }
-                                });
-                                responses.push(error_response);
+                    // Issue all requests at once and build a response per request
+                    let results = transport.request_all(requests).await;
+                    let responses = batch
+                        .iter()
+                        .zip(results)
+                        .map(|(single, result)| {
+                            let id = transport.response_id(single.id());
+                            match result {
+                                Ok(result) => success_response(id, &result),
+                                Err(e) => Ok(error_response(id, &e)),
                             }
-                        }
-                    }
+                        })
+                        .collect::<std::result::Result<_, _>>()?;
 
-                    let response_packet = serde_json::from_value(Value::Array(responses))
-                        .map_err(|e| TransportError::local_usage(e))?;
-                    Ok(ResponsePacket::Batch(response_packet))
+                    Ok(ResponsePacket::Batch(responses))
                 }
             }
         })
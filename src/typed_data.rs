@@ -0,0 +1,231 @@
+//! Client-side validation of EIP-712 typed data before it reaches the wallet
+
+use serde_json::{Map, Value};
+
+use crate::error::{Result, WindowError};
+
+/// Check typed data is well-formed
+///
+/// Verifies that `primaryType` is declared in `types`, that every type a
+/// field references is either an EIP-712 atomic type or declared, and that
+/// `message` (including nested structs and arrays) has exactly the fields of
+/// its type. Wallets report malformed typed data poorly, if at all.
+pub(crate) fn validate_typed_data(typed_data: &Value) -> Result<()> {
+    let types = typed_data["types"]
+        .as_object()
+        .ok_or_else(|| invalid("`types` must be an object"))?;
+    let primary_type = typed_data["primaryType"]
+        .as_str()
+        .ok_or_else(|| invalid("`primaryType` must be a string"))?;
+
+    for (name, fields) in types {
+        for (field, ty) in struct_fields(name, fields)? {
+            let base = base_type(ty);
+            if !is_atomic(base) && !types.contains_key(base) {
+                return Err(invalid(format!(
+                    "type `{}` of `{}.{}` is not defined",
+                    base, name, field
+                )));
+            }
+        }
+    }
+
+    if !types.contains_key(primary_type) {
+        return Err(invalid(format!(
+            "primary type `{}` is not defined in `types`",
+            primary_type
+        )));
+    }
+
+    validate_value(types, primary_type, &typed_data["message"], "message")
+}
+
+/// Check `value` matches the declared type `ty`, `path` locating it for errors
+fn validate_value(types: &Map<String, Value>, ty: &str, value: &Value, path: &str) -> Result<()> {
+    if let Some(element) = array_element(ty) {
+        let items = value
+            .as_array()
+            .ok_or_else(|| invalid(format!("`{}` must be an array of `{}`", path, element)))?;
+        return items.iter().enumerate().try_for_each(|(i, item)| {
+            validate_value(types, element, item, &format!("{}[{}]", path, i))
+        });
+    }
+
+    let Some(fields) = types.get(ty) else {
+        // Atomic value; its encoding is left to the wallet
+        return Ok(());
+    };
+    let fields = struct_fields(ty, fields)?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| invalid(format!("`{}` must be a `{}` object", path, ty)))?;
+
+    for (field, field_ty) in &fields {
+        let value = object.get(*field).ok_or_else(|| {
+            invalid(format!(
+                "`{}` is missing field `{}` of `{}`",
+                path, field, ty
+            ))
+        })?;
+        validate_value(types, field_ty, value, &format!("{}.{}", path, field))?;
+    }
+    if let Some(extra) = object
+        .keys()
+        .find(|key| !fields.iter().any(|(f, _)| f == key))
+    {
+        return Err(invalid(format!(
+            "`{}` has field `{}` not declared by `{}`",
+            path, extra, ty
+        )));
+    }
+    Ok(())
+}
+
+/// Read the `[{ name, type }]` field list of a struct type
+fn struct_fields<'a>(name: &str, fields: &'a Value) -> Result<Vec<(&'a str, &'a str)>> {
+    fields
+        .as_array()
+        .ok_or_else(|| invalid(format!("type `{}` must be an array of fields", name)))?
+        .iter()
+        .map(
+            |field| match (field["name"].as_str(), field["type"].as_str()) {
+                (Some(field), Some(ty)) => Ok((field, ty)),
+                _ => Err(invalid(format!(
+                    "type `{}` has a field without `name` and `type`",
+                    name
+                ))),
+            },
+        )
+        .collect()
+}
+
+/// Element type of an array type (`Foo[]` or `Foo[3]`), `None` otherwise
+fn array_element(ty: &str) -> Option<&str> {
+    ty.strip_suffix(']')
+        .and_then(|ty| ty.rsplit_once('['))
+        .map(|(element, _)| element)
+}
+
+/// Type with all array suffixes removed
+fn base_type(mut ty: &str) -> &str {
+    while let Some(element) = array_element(ty) {
+        ty = element;
+    }
+    ty
+}
+
+/// Whether `ty` is an EIP-712 atomic or dynamic type rather than a struct
+fn is_atomic(ty: &str) -> bool {
+    let sized = |prefix: &str, valid: fn(u32) -> bool| {
+        ty.strip_prefix(prefix)
+            .and_then(|size| size.parse().ok())
+            .is_some_and(valid)
+    };
+
+    matches!(ty, "address" | "bool" | "string" | "bytes")
+        || sized("bytes", |n| (1..=32).contains(&n))
+        || sized("uint", |n| n.is_multiple_of(8) && (8..=256).contains(&n))
+        || sized("int", |n| n.is_multiple_of(8) && (8..=256).contains(&n))
+}
+
+/// Build a [`WindowError::InvalidTypedData`] error
+fn invalid(reason: impl Into<String>) -> WindowError {
+    WindowError::InvalidTypedData(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mail(message: Value) -> Value {
+        json!({
+            "types": {
+                "EIP712Domain": [{ "name": "name", "type": "string" }],
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallets", "type": "address[]" },
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person[2]" },
+                    { "name": "contents", "type": "string" },
+                ],
+            },
+            "primaryType": "Mail",
+            "domain": { "name": "Ether Mail" },
+            "message": message,
+        })
+    }
+
+    fn person() -> Value {
+        json!({ "name": "Bob", "wallets": ["0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"] })
+    }
+
+    fn reason(typed_data: &Value) -> String {
+        match validate_typed_data(typed_data) {
+            Err(WindowError::InvalidTypedData(reason)) => reason,
+            other => panic!("expected InvalidTypedData, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_typed_data() {
+        let typed_data = mail(json!({
+            "from": person(),
+            "to": [person(), person()],
+            "contents": "Hello",
+        }));
+        assert!(validate_typed_data(&typed_data).is_ok());
+    }
+
+    #[test]
+    fn rejects_missing_and_extra_fields() {
+        let missing = mail(json!({ "from": person(), "to": [] }));
+        assert!(reason(&missing).contains("missing field `contents`"));
+
+        let extra = mail(json!({
+            "from": { "name": "Bob", "wallets": [], "age": 1 },
+            "to": [],
+            "contents": "Hello",
+        }));
+        assert!(reason(&extra).contains("`message.from` has field `age`"));
+    }
+
+    #[test]
+    fn rejects_mistyped_values() {
+        let not_array = mail(json!({ "from": person(), "to": person(), "contents": "Hello" }));
+        assert!(reason(&not_array).contains("`message.to` must be an array"));
+
+        let not_struct = mail(json!({ "from": "Bob", "to": [], "contents": "Hello" }));
+        assert!(reason(&not_struct).contains("`message.from` must be a `Person` object"));
+    }
+
+    #[test]
+    fn rejects_undefined_types() {
+        let mut typed_data = mail(json!({}));
+        typed_data["types"]["Mail"][2]["type"] = json!("Text");
+        assert!(reason(&typed_data).contains("type `Text` of `Mail.contents` is not defined"));
+
+        let mut typed_data = mail(json!({}));
+        typed_data["primaryType"] = json!("Letter");
+        assert!(reason(&typed_data).contains("primary type `Letter`"));
+
+        assert!(reason(&json!({ "primaryType": "Mail" })).contains("`types`"));
+    }
+
+    #[test]
+    fn recognizes_atomic_types() {
+        for ty in [
+            "address", "bool", "string", "bytes", "bytes32", "uint8", "int256",
+        ] {
+            assert!(is_atomic(ty), "{}", ty);
+        }
+        for ty in ["bytes0", "bytes33", "uint7", "uint264", "int", "Person"] {
+            assert!(!is_atomic(ty), "{}", ty);
+        }
+        assert_eq!(base_type("Person[2][]"), "Person");
+        assert_eq!(array_element("uint256[3]"), Some("uint256"));
+        assert_eq!(array_element("uint256"), None);
+    }
+}
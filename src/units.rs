@@ -0,0 +1,226 @@
+//! Ethereum quantity encoding and unit formatting helpers
+
+use alloy_primitives::U256;
+
+use crate::error::{Result, WindowError};
+
+/// Encode a value as an Ethereum JSON-RPC quantity
+///
+/// Quantities are `0x`-prefixed hex without leading zeros, and zero is
+/// encoded as `0x0`.
+pub fn to_hex_quantity(value: U256) -> String {
+    format!("{:#x}", value)
+}
+
+/// Decode an Ethereum JSON-RPC quantity
+///
+/// The `0x` prefix is required and at least one digit must follow it.
+/// Odd-length values (`0x1`) are valid quantities. Leading zeros are not
+/// allowed by the spec but some wallets emit them, so they are accepted.
+/// Values wider than 256 bits are rejected.
+pub fn from_hex_quantity(s: &str) -> Result<U256> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .ok_or_else(|| WindowError::InvalidQuantity(format!("{} (missing 0x prefix)", s)))?;
+    if digits.is_empty() {
+        return Err(WindowError::InvalidQuantity(format!("{} (no digits)", s)));
+    }
+
+    U256::from_str_radix(digits, 16)
+        .map_err(|e| WindowError::InvalidQuantity(format!("{} ({})", s, e)))
+}
+
+/// Number of decimals of ether, in wei
+const ETHER_DECIMALS: usize = 18;
+
+/// Format a wei amount as decimal ether with at most `decimals` fraction digits
+///
+/// Uses exact integer arithmetic, so arbitrarily large amounts format without
+/// precision loss. Extra digits are truncated rather than rounded, so a
+/// balance is never overstated, and trailing zeros are trimmed:
+/// `format_ether(U256::from(1_234_500_000_000_000_000u64), 4)` is `"1.2345"`
+/// and a whole amount formats without a fraction (`"2"`).
+pub fn format_ether(wei: U256, decimals: usize) -> String {
+    format_decimal(wei, ETHER_DECIMALS, decimals)
+}
+
+/// Number of decimals of gwei, in wei
+const GWEI_DECIMALS: usize = 9;
+
+/// Format a wei amount as decimal gwei, e.g. for gas prices
+///
+/// Exact like [`format_ether`], keeping every significant digit:
+/// `format_gwei(U256::from(1_500_000_001u64))` is `"1.500000001"`, and
+/// sub-gwei amounts format as fractions (`"0.000000001"` for 1 wei).
+pub fn format_gwei(wei: U256) -> String {
+    format_decimal(wei, GWEI_DECIMALS, GWEI_DECIMALS)
+}
+
+/// Parse a decimal gwei amount, e.g. a user-entered gas price, into wei
+///
+/// Accepts whole numbers and up to 9 fraction digits (`"1.5"`, `"0.000000001"`).
+/// More precise amounts, signs, exponents and amounts overflowing `U256` are
+/// rejected with [`WindowError::InvalidQuantity`].
+pub fn parse_gwei(s: &str) -> Result<U256> {
+    parse_decimal(s, GWEI_DECIMALS)
+}
+
+/// Parse a decimal string scaled up by `10^unit`, rejecting lost precision
+fn parse_decimal(s: &str, unit: usize) -> Result<U256> {
+    let invalid = |reason: &str| WindowError::InvalidQuantity(format!("{} ({})", s, reason));
+
+    let (whole, fraction) = s.trim().split_once('.').unwrap_or((s.trim(), ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(invalid("no digits"));
+    }
+    if !whole
+        .bytes()
+        .chain(fraction.bytes())
+        .all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid("not a decimal number"));
+    }
+    if fraction.len() > unit {
+        return Err(invalid(&format!("more than {} decimals", unit)));
+    }
+
+    let digits = format!("{}{:0<width$}", whole, fraction, width = unit);
+    U256::from_str_radix(&digits, 10).map_err(|_| invalid("too large"))
+}
+
+/// Format a token amount with the token's `decimals`, keeping every fraction digit
+///
+/// `1_500_000` with 6 decimals formats as `"1.500000"`. Tokens report their
+/// own decimals, so more than 77 (where `10^decimals` overflows a `U256`) is
+/// rejected rather than formatted as garbage.
+pub(crate) fn format_token_amount(amount: U256, decimals: u8) -> Result<String> {
+    let scale = U256::from(10)
+        .checked_pow(U256::from(decimals))
+        .ok_or_else(|| {
+            WindowError::InvalidQuantity(format!(
+                "token has {} decimals, at most 77 are supported",
+                decimals
+            ))
+        })?;
+    let (whole, fraction) = amount.div_rem(scale);
+
+    if decimals == 0 {
+        return Ok(whole.to_string());
+    }
+    Ok(format!(
+        "{}.{:0>width$}",
+        whole,
+        fraction,
+        width = usize::from(decimals)
+    ))
+}
+
+/// Format `value` scaled down by `10^unit` with at most `precision` fraction digits
+fn format_decimal(value: U256, unit: usize, precision: usize) -> String {
+    let scale = U256::from(10).pow(U256::from(unit));
+    let (whole, fraction) = value.div_rem(scale);
+
+    let fraction = format!("{:0>width$}", fraction, width = unit);
+    let fraction = fraction[..precision.min(unit)].trim_end_matches('0');
+
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_hex_quantities() {
+        assert_eq!(to_hex_quantity(U256::ZERO), "0x0");
+        assert_eq!(to_hex_quantity(U256::from(21000)), "0x5208");
+    }
+
+    #[test]
+    fn decodes_hex_quantities() {
+        assert_eq!(from_hex_quantity("0x5208").unwrap(), U256::from(21000));
+        assert_eq!(from_hex_quantity("0X1").unwrap(), U256::from(1));
+        assert_eq!(from_hex_quantity("0x0001").unwrap(), U256::from(1));
+        for invalid in ["5208", "0x", "0xzz", &format!("0x1{}", "0".repeat(64))] {
+            assert!(matches!(
+                from_hex_quantity(invalid),
+                Err(WindowError::InvalidQuantity(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn formats_ether() {
+        let wei = U256::from(1_234_567_800_000_000_000u64);
+        assert_eq!(format_ether(wei, 4), "1.2345");
+        assert_eq!(format_ether(wei, 18), "1.2345678");
+        assert_eq!(format_ether(wei, 0), "1");
+        assert_eq!(
+            format_ether(U256::from(2) * U256::from(10).pow(U256::from(18)), 4),
+            "2"
+        );
+        assert_eq!(format_ether(U256::from(1), 4), "0");
+        assert_eq!(format_ether(U256::from(1), 30), "0.000000000000000001");
+        assert!(format_ether(U256::MAX, 2)
+            .starts_with("115792089237316195423570985008687907853269984665640564039457"));
+    }
+
+    #[test]
+    fn formats_gwei() {
+        assert_eq!(format_gwei(U256::from(1_500_000_001u64)), "1.500000001");
+        assert_eq!(format_gwei(U256::from(30_000_000_000u64)), "30");
+        assert_eq!(format_gwei(U256::from(1)), "0.000000001");
+        assert_eq!(format_gwei(U256::ZERO), "0");
+    }
+
+    #[test]
+    fn parses_gwei() {
+        assert_eq!(parse_gwei("1.5").unwrap(), U256::from(1_500_000_000u64));
+        assert_eq!(parse_gwei(" 30 ").unwrap(), U256::from(30_000_000_000u64));
+        assert_eq!(parse_gwei(".5").unwrap(), U256::from(500_000_000u64));
+        assert_eq!(parse_gwei("0.000000001").unwrap(), U256::from(1));
+        for invalid in [
+            "",
+            ".",
+            "-1",
+            "1e9",
+            "0.0000000001",
+            "1.2.3",
+            &"9".repeat(80),
+        ] {
+            assert!(
+                matches!(parse_gwei(invalid), Err(WindowError::InvalidQuantity(_))),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn formats_token_amounts() {
+        let amount = U256::from(1_500_000u64);
+        assert_eq!(format_token_amount(amount, 6).unwrap(), "1.500000");
+        assert_eq!(format_token_amount(amount, 0).unwrap(), "1500000");
+        assert_eq!(
+            format_token_amount(U256::from(5), 18).unwrap(),
+            "0.000000000000000005"
+        );
+        let max = format_token_amount(U256::MAX, 77).unwrap();
+        assert!(max.starts_with("1.15792"));
+        assert_eq!(max.len(), 2 + 77);
+    }
+
+    #[test]
+    fn rejects_overflowing_decimals() {
+        assert!(matches!(
+            format_token_amount(U256::from(1), 78),
+            Err(WindowError::InvalidQuantity(_))
+        ));
+        assert!(format_token_amount(U256::from(1), u8::MAX).is_err());
+    }
+}
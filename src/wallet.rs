@@ -0,0 +1,416 @@
+//! Wallet-managed assets and chains (EIP-747, EIP-3085)
+
+use alloy_primitives::{Address, U256};
+use serde_json::{json, Value};
+
+use crate::error::{Result, WindowError};
+#[cfg(target_arch = "wasm32")]
+use crate::provider::WindowProvider;
+use crate::units::to_hex_quantity;
+
+/// Token to suggest to the wallet with `wallet_watchAsset` (EIP-747)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchAssetParams {
+    /// ERC-20 token contract
+    pub address: Address,
+    /// Ticker symbol, at most 11 characters for most wallets
+    pub symbol: String,
+    /// Token decimals
+    pub decimals: u8,
+    /// Token icon as an `http(s)` URL or a base64 `data:image/...` URI
+    pub image: Option<String>,
+}
+
+/// Native currency of a chain added with `wallet_addEthereumChain`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeCurrency {
+    /// Currency name, e.g. `Ether`
+    pub name: String,
+    /// Currency symbol, e.g. `ETH`
+    pub symbol: String,
+    /// Currency decimals, 18 for nearly every chain
+    pub decimals: u8,
+}
+
+/// Chain to add to the wallet with `wallet_addEthereumChain` (EIP-3085)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddEthereumChainParameter {
+    /// Chain ID, sent as a hex quantity
+    pub chain_id: u64,
+    /// Human-readable chain name
+    pub chain_name: String,
+    /// Native currency of the chain
+    pub native_currency: NativeCurrency,
+    /// RPC endpoints, the first one is preferred
+    pub rpc_urls: Vec<String>,
+    /// Block explorer URLs
+    pub block_explorer_urls: Vec<String>,
+    /// Chain icons as `http(s)` URLs or base64 `data:image/...` URIs
+    pub icon_urls: Vec<String>,
+}
+
+impl WatchAssetParams {
+    /// Build the `wallet_watchAsset` params object
+    fn to_params(&self) -> Value {
+        let mut options = json!({
+            "address": self.address,
+            "symbol": self.symbol,
+            "decimals": self.decimals,
+        });
+        if let Some(image) = &self.image {
+            options["image"] = json!(image);
+        }
+        json!({ "type": "ERC20", "options": options })
+    }
+}
+
+impl AddEthereumChainParameter {
+    /// Build the `wallet_addEthereumChain` params object
+    fn to_params(&self) -> Value {
+        let mut params = json!({
+            "chainId": to_hex_quantity(U256::from(self.chain_id)),
+            "chainName": self.chain_name,
+            "nativeCurrency": {
+                "name": self.native_currency.name,
+                "symbol": self.native_currency.symbol,
+                "decimals": self.native_currency.decimals,
+            },
+            "rpcUrls": self.rpc_urls,
+        });
+        // Some wallets reject empty arrays, so omit unset optional fields
+        if !self.block_explorer_urls.is_empty() {
+            params["blockExplorerUrls"] = json!(self.block_explorer_urls);
+        }
+        if !self.icon_urls.is_empty() {
+            params["iconUrls"] = json!(self.icon_urls);
+        }
+        params
+    }
+}
+
+/// EIP-3085 code for a chain the wallet doesn't know
+#[cfg(target_arch = "wasm32")]
+const UNRECOGNIZED_CHAIN: i64 = 4902;
+
+/// Outcome of [`WindowProvider::switch_chain_or_add`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwitchChainOutcome {
+    /// The wallet switched to the chain
+    Switched,
+    /// The wallet didn't know the chain, added it and switched to it
+    AddedAndSwitched,
+    /// The user declined the switch or the addition
+    Declined,
+}
+
+/// Check an icon is an `http(s)` URL or a base64-encoded `data:image/...` URI
+fn validate_image_uri(uri: &str) -> Result<()> {
+    if uri.starts_with("https://") || uri.starts_with("http://") {
+        return Ok(());
+    }
+
+    let Some(data) = uri.strip_prefix("data:") else {
+        let scheme = uri.split(':').next().unwrap_or(uri);
+        return Err(WindowError::InvalidImage(format!(
+            "unsupported scheme `{}`, expected http(s) or data URI",
+            scheme
+        )));
+    };
+
+    let (header, payload) = data
+        .split_once(',')
+        .ok_or_else(|| WindowError::InvalidImage("data URI has no payload".to_string()))?;
+    let mime = header
+        .strip_suffix(";base64")
+        .ok_or_else(|| WindowError::InvalidImage("data URI must be base64-encoded".to_string()))?;
+    if !mime.starts_with("image/") || mime.len() == "image/".len() {
+        return Err(WindowError::InvalidImage(format!(
+            "data URI MIME type `{}` is not an image",
+            mime
+        )));
+    }
+    if !is_base64(payload) {
+        return Err(WindowError::InvalidImage(
+            "data URI payload is not valid base64".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `s` is padded standard base64
+fn is_base64(s: &str) -> bool {
+    let data = s.trim_end_matches('=');
+    !s.is_empty()
+        && s.len().is_multiple_of(4)
+        && s.len() - data.len() <= 2
+        && data
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/')
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WindowProvider {
+    /// Ask the wallet to track an ERC-20 token (`wallet_watchAsset`)
+    ///
+    /// Returns whether the user added the token. The image is validated
+    /// before anything is sent to the wallet.
+    pub async fn watch_asset(&self, asset: &WatchAssetParams) -> Result<bool> {
+        if let Some(image) = &asset.image {
+            validate_image_uri(image)?;
+        }
+        self.request("wallet_watchAsset", asset.to_params()).await
+    }
+
+    /// Ask the wallet to add a chain (`wallet_addEthereumChain`)
+    ///
+    /// Icons are validated before anything is sent to the wallet. Most
+    /// wallets also switch to the chain once it's added.
+    pub async fn add_chain(&self, chain: &AddEthereumChainParameter) -> Result<()> {
+        for icon in &chain.icon_urls {
+            validate_image_uri(icon)?;
+        }
+        self.request::<Value>("wallet_addEthereumChain", json!([chain.to_params()]))
+            .await?;
+        Ok(())
+    }
+
+    /// Ask the wallet to switch chains, adding the chain first only if allowed
+    ///
+    /// If the wallet doesn't know `chain_id` (error 4902), `add` decides what
+    /// happens: with `None` the 4902 error is returned, so a dapp that only
+    /// meant to switch never prompts the user to add a network; with `Some`,
+    /// the chain is added with [`add_chain`](Self::add_chain) and switched to.
+    /// A rejected prompt is reported as [`SwitchChainOutcome::Declined`].
+    pub async fn switch_chain_or_add(
+        &self,
+        chain_id: u64,
+        add: Option<AddEthereumChainParameter>,
+    ) -> Result<SwitchChainOutcome> {
+        match self.switch_chain(chain_id).await {
+            Ok(()) => return Ok(SwitchChainOutcome::Switched),
+            Err(WindowError::UserRejected) => return Ok(SwitchChainOutcome::Declined),
+            Err(e) if e.code() != Some(UNRECOGNIZED_CHAIN) => return Err(e),
+            Err(e) => {
+                let Some(chain) = add else {
+                    return Err(e);
+                };
+                if chain.chain_id != chain_id {
+                    return Err(WindowError::InvalidParams(format!(
+                        "chain to add has ID {}, expected {}",
+                        chain.chain_id, chain_id
+                    )));
+                }
+                match self.add_chain(&chain).await {
+                    Ok(()) => {}
+                    Err(WindowError::UserRejected) => return Ok(SwitchChainOutcome::Declined),
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        // Most wallets switch when adding, in which case this is a no-op
+        match self.switch_chain(chain_id).await {
+            Ok(()) => Ok(SwitchChainOutcome::AddedAndSwitched),
+            Err(WindowError::UserRejected) => Ok(SwitchChainOutcome::Declined),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Send `wallet_switchEthereumChain`
+    pub(crate) async fn switch_chain(&self, chain_id: u64) -> Result<()> {
+        let params = json!([{ "chainId": to_hex_quantity(U256::from(chain_id)) }]);
+        self.request::<Value>("wallet_switchEthereumChain", params)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn polygon() -> AddEthereumChainParameter {
+        AddEthereumChainParameter {
+            chain_id: 137,
+            chain_name: "Polygon".to_string(),
+            native_currency: NativeCurrency {
+                name: "POL".to_string(),
+                symbol: "POL".to_string(),
+                decimals: 18,
+            },
+            rpc_urls: vec!["https://polygon-rpc.com".to_string()],
+            block_explorer_urls: Vec::new(),
+            icon_urls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn validates_image_uris() {
+        assert!(validate_image_uri("https://example.com/icon.png").is_ok());
+        assert!(validate_image_uri("http://localhost/icon.svg").is_ok());
+        assert!(validate_image_uri("data:image/png;base64,iVBORw0KGgo=").is_ok());
+        for invalid in [
+            "ipfs://Qm",
+            "data:image/png,raw",
+            "data:text/plain;base64,aGk=",
+            "data:image/;base64,aGk=",
+            "data:image/png;base64,not base64",
+            "data:image/png",
+        ] {
+            assert!(
+                matches!(
+                    validate_image_uri(invalid),
+                    Err(WindowError::InvalidImage(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn recognizes_base64() {
+        assert!(is_base64("aGk="));
+        assert!(is_base64("aGVsbG8h"));
+        assert!(is_base64("a+/="));
+        for invalid in ["", "aGk", "aG===", "aGk!", "a=Gk"] {
+            assert!(!is_base64(invalid), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn builds_watch_asset_params() {
+        let token = Address::repeat_byte(0xaa);
+        let mut asset = WatchAssetParams {
+            address: token,
+            symbol: "USDC".to_string(),
+            decimals: 6,
+            image: None,
+        };
+        assert_eq!(
+            asset.to_params(),
+            json!({
+                "type": "ERC20",
+                "options": { "address": token, "symbol": "USDC", "decimals": 6 },
+            })
+        );
+
+        asset.image = Some("https://example.com/usdc.png".to_string());
+        assert_eq!(
+            asset.to_params()["options"]["image"],
+            "https://example.com/usdc.png"
+        );
+    }
+
+    #[test]
+    fn omits_empty_chain_fields() {
+        let params = polygon().to_params();
+        assert_eq!(
+            params,
+            json!({
+                "chainId": "0x89",
+                "chainName": "Polygon",
+                "nativeCurrency": { "name": "POL", "symbol": "POL", "decimals": 18 },
+                "rpcUrls": ["https://polygon-rpc.com"],
+            })
+        );
+
+        let chain = AddEthereumChainParameter {
+            block_explorer_urls: vec!["https://polygonscan.com".to_string()],
+            icon_urls: vec!["https://example.com/pol.png".to_string()],
+            ..polygon()
+        };
+        let params = chain.to_params();
+        assert_eq!(
+            params["blockExplorerUrls"],
+            json!(["https://polygonscan.com"])
+        );
+        assert_eq!(params["iconUrls"], json!(["https://example.com/pol.png"]));
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::tests::polygon;
+    use super::*;
+    use crate::mock::{rpc_error, MockWallet};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    /// Wallet that doesn't know any chain until one is added
+    fn wallet() -> MockWallet {
+        let added = Rc::new(Cell::new(false));
+        MockWallet::new(move |method, _| match method {
+            "wallet_switchEthereumChain" if added.get() => Ok(Value::Null),
+            "wallet_switchEthereumChain" => Err(rpc_error(4902, "Unrecognized chain ID")),
+            "wallet_addEthereumChain" => {
+                added.set(true);
+                Ok(Value::Null)
+            }
+            _ => Err(rpc_error(4200, "unsupported")),
+        })
+    }
+
+    fn methods(wallet: &MockWallet) -> Vec<String> {
+        wallet
+            .requests()
+            .into_iter()
+            .map(|(method, _)| method)
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    async fn adds_unknown_chains_then_switches() {
+        let wallet = wallet();
+        let provider = WindowProvider::from_transport(wallet.transport());
+
+        let outcome = provider
+            .switch_chain_or_add(137, Some(polygon()))
+            .await
+            .unwrap();
+        assert_eq!(outcome, SwitchChainOutcome::AddedAndSwitched);
+        assert_eq!(
+            methods(&wallet),
+            [
+                "wallet_switchEthereumChain",
+                "wallet_addEthereumChain",
+                "wallet_switchEthereumChain"
+            ]
+        );
+        assert_eq!(wallet.requests()[0].1, json!([{ "chainId": "0x89" }]));
+    }
+
+    #[wasm_bindgen_test]
+    async fn returns_unrecognized_chain_without_add() {
+        let wallet = wallet();
+        let provider = WindowProvider::from_transport(wallet.transport());
+
+        let error = provider.switch_chain_or_add(137, None).await.unwrap_err();
+        assert_eq!(error.code(), Some(UNRECOGNIZED_CHAIN));
+        assert_eq!(methods(&wallet), ["wallet_switchEthereumChain"]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn rejects_chain_to_add_with_other_id() {
+        let wallet = wallet();
+        let provider = WindowProvider::from_transport(wallet.transport());
+
+        let error = provider
+            .switch_chain_or_add(10, Some(polygon()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, WindowError::InvalidParams(_)));
+        // Nothing is added
+        assert_eq!(methods(&wallet), ["wallet_switchEthereumChain"]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn reports_declined_switches() {
+        let wallet = MockWallet::new(|_, _| Err(rpc_error(4001, "User rejected the request")));
+        let provider = WindowProvider::from_transport(wallet.transport());
+
+        let outcome = provider.switch_chain_or_add(137, None).await.unwrap();
+        assert_eq!(outcome, SwitchChainOutcome::Declined);
+    }
+}
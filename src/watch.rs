@@ -0,0 +1,333 @@
+//! Transaction status streaming for pending-transaction UIs
+
+use std::time::Duration;
+
+use alloy_primitives::{Address, Bytes, TxHash, U128, U256, U64};
+use alloy_rpc_types_eth::TransactionReceipt;
+use futures::Stream;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::js_bindings::sleep;
+use crate::provider::{WindowProvider, RECEIPT_POLL_INTERVAL};
+
+/// Status update emitted by [`WindowProvider::watch_transaction`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum TxStatus {
+    /// The transaction was submitted and is waiting to be mined
+    Submitted,
+    /// The transaction was mined and has this many confirmations so far
+    Pending(u64),
+    /// The transaction reached the requested number of confirmations
+    Confirmed(Box<TransactionReceipt>),
+    /// The transaction will not confirm
+    Failed(TxFailure),
+}
+
+/// Reason a watched transaction failed
+#[derive(Clone, Debug, PartialEq)]
+pub enum TxFailure {
+    /// The transaction was mined but reverted
+    Reverted(Box<TransactionReceipt>),
+    /// Another transaction with the same sender and nonce was mined instead
+    /// (e.g. the user sped up or cancelled it in their wallet)
+    Replaced,
+}
+
+/// Submitted parameters of a transaction, as far as the wallet reports them
+///
+/// Returned by [`WindowProvider::get_pending_transaction`]. Wallets often
+/// return partial objects for transactions that aren't mined yet (no
+/// signature, no block fields, sometimes no fee fields), so everything but
+/// the hash, value and input is optional.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingTransaction {
+    /// Transaction hash
+    pub hash: TxHash,
+    /// Sender
+    pub from: Option<Address>,
+    /// Recipient, `None` for contract creations
+    pub to: Option<Address>,
+    /// Value transferred in wei, zero if not reported
+    pub value: U256,
+    /// Calldata, empty if not reported
+    pub input: Bytes,
+    /// Sender nonce
+    pub nonce: Option<u64>,
+    /// Gas limit
+    pub gas: Option<u64>,
+    /// Gas price of legacy transactions, effective gas price once mined
+    pub gas_price: Option<u128>,
+    /// EIP-1559 max fee per gas
+    pub max_fee_per_gas: Option<u128>,
+    /// EIP-1559 max priority fee per gas
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// Block the transaction was mined in, `None` while pending
+    pub block_number: Option<u64>,
+}
+
+/// `eth_getTransactionByHash` result with every field optional
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPendingTransaction {
+    hash: Option<TxHash>,
+    from: Option<Address>,
+    to: Option<Address>,
+    value: Option<U256>,
+    #[serde(alias = "data")]
+    input: Option<Bytes>,
+    nonce: Option<U64>,
+    gas: Option<U64>,
+    gas_price: Option<U128>,
+    max_fee_per_gas: Option<U128>,
+    max_priority_fee_per_gas: Option<U128>,
+    block_number: Option<U64>,
+}
+
+impl RawPendingTransaction {
+    /// Fill in the defaults, taking `hash` if the wallet omitted it
+    fn into_pending(self, hash: TxHash) -> PendingTransaction {
+        PendingTransaction {
+            hash: self.hash.unwrap_or(hash),
+            from: self.from,
+            to: self.to,
+            value: self.value.unwrap_or_default(),
+            input: self.input.unwrap_or_default(),
+            nonce: self.nonce.map(|n| n.to()),
+            gas: self.gas.map(|n| n.to()),
+            gas_price: self.gas_price.map(|n| n.to()),
+            max_fee_per_gas: self.max_fee_per_gas.map(|n| n.to()),
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas.map(|n| n.to()),
+            block_number: self.block_number.map(|n| n.to()),
+        }
+    }
+}
+
+/// Progress of a watched transaction between polls
+enum WatchState {
+    Start,
+    Polling {
+        /// Sender and nonce, once the node has seen the transaction
+        sender: Option<(Address, u64)>,
+        /// Confirmations last reported
+        confirmations: u64,
+    },
+    Done,
+}
+
+impl WindowProvider {
+    /// Watch a submitted transaction until it has `confirmations` confirmations
+    ///
+    /// Emits [`TxStatus::Submitted`] first, then [`TxStatus::Pending`] each time
+    /// the confirmation count grows, and ends with either
+    /// [`TxStatus::Confirmed`] or [`TxStatus::Failed`]. RPC errors while polling
+    /// are treated as transient and retried on the next poll.
+    ///
+    /// # Replacement detection
+    /// Wallets let users speed up or cancel a transaction by re-sending the
+    /// same nonce with a different hash, so the original hash never gets a
+    /// receipt. While no receipt exists, the sender and nonce are read from
+    /// `eth_getTransactionByHash`; once the sender's mined nonce
+    /// (`eth_getTransactionCount(.., "latest")`) moves past that nonce and the
+    /// receipt is still missing, the transaction is reported as
+    /// [`TxFailure::Replaced`]. A transaction the node never saw cannot be
+    /// checked and is polled until it appears.
+    pub fn watch_transaction(
+        &self,
+        hash: TxHash,
+        confirmations: u64,
+    ) -> impl Stream<Item = TxStatus> {
+        let provider = self.clone();
+        let confirmations = confirmations.max(1);
+
+        futures::stream::unfold(WatchState::Start, move |state| {
+            let provider = provider.clone();
+            async move {
+                match state {
+                    WatchState::Start => Some((
+                        TxStatus::Submitted,
+                        WatchState::Polling {
+                            sender: None,
+                            confirmations: 0,
+                        },
+                    )),
+                    WatchState::Polling {
+                        mut sender,
+                        confirmations: mut reported,
+                    } => loop {
+                        match provider.poll_transaction(hash, &mut sender).await {
+                            Ok(TxPoll::Mined(receipt)) if !receipt.status() => {
+                                return Some((
+                                    TxStatus::Failed(TxFailure::Reverted(receipt)),
+                                    WatchState::Done,
+                                ));
+                            }
+                            Ok(TxPoll::Mined(receipt)) => {
+                                let mined = receipt.block_number.unwrap_or_default();
+                                let current = provider.block_number().await.unwrap_or(mined);
+                                let count = current.saturating_sub(mined) + 1;
+
+                                if count >= confirmations {
+                                    return Some((TxStatus::Confirmed(receipt), WatchState::Done));
+                                }
+                                if count > reported {
+                                    reported = count;
+                                    return Some((
+                                        TxStatus::Pending(count),
+                                        WatchState::Polling {
+                                            sender,
+                                            confirmations: reported,
+                                        },
+                                    ));
+                                }
+                            }
+                            Ok(TxPoll::Replaced) => {
+                                return Some((
+                                    TxStatus::Failed(TxFailure::Replaced),
+                                    WatchState::Done,
+                                ));
+                            }
+                            Ok(TxPoll::Waiting) => {}
+                            Err(e) => tracing::debug!("Polling transaction {} failed: {}", hash, e),
+                        }
+                        sleep(RECEIPT_POLL_INTERVAL).await;
+                    },
+                    WatchState::Done => None,
+                }
+            }
+        })
+    }
+
+    /// Wait until a transaction has `confirmations` confirmations
+    ///
+    /// Polls the receipt and then the block number every `poll_interval`. The
+    /// block that includes the transaction counts as the first confirmation.
+    /// The receipt is re-fetched on every poll, so if a reorg un-mines the
+    /// transaction the wait continues until it is mined again, and the final
+    /// receipt reflects the block it ended up in. Check its status for reverts.
+    pub async fn wait_for_confirmations(
+        &self,
+        hash: TxHash,
+        confirmations: u64,
+        poll_interval: Duration,
+    ) -> crate::Result<TransactionReceipt> {
+        loop {
+            if let Some(receipt) = self.get_transaction_receipt(hash).await? {
+                let mined = receipt.block_number.unwrap_or_default();
+                let current = self.block_number().await?;
+                if current.saturating_sub(mined) + 1 >= confirmations {
+                    return Ok(receipt);
+                }
+            }
+            sleep(poll_interval).await;
+        }
+    }
+
+    /// Get the submitted parameters of a transaction by hash
+    ///
+    /// Unlike [`get_transaction`](Self::get_transaction), this accepts the
+    /// partial objects wallets return for transactions that aren't mined yet,
+    /// so "waiting for confirmation" UIs can show the gas and value actually
+    /// submitted. Returns `None` while the transaction is unknown to the
+    /// wallet's node.
+    pub async fn get_pending_transaction(
+        &self,
+        hash: TxHash,
+    ) -> crate::Result<Option<PendingTransaction>> {
+        let tx: Option<RawPendingTransaction> = self
+            .request("eth_getTransactionByHash", json!([hash]))
+            .await?;
+        Ok(tx.map(|tx| tx.into_pending(hash)))
+    }
+
+    /// Check once whether the transaction was mined or replaced
+    async fn poll_transaction(
+        &self,
+        hash: TxHash,
+        sender: &mut Option<(Address, u64)>,
+    ) -> crate::Result<TxPoll> {
+        if let Some(receipt) = self.get_transaction_receipt(hash).await? {
+            return Ok(TxPoll::Mined(Box::new(receipt)));
+        }
+
+        // Wallets return partial objects for pending transactions, which the
+        // strict `get_transaction` would fail to parse
+        if let Some(PendingTransaction {
+            from: Some(from),
+            nonce: Some(nonce),
+            ..
+        }) = self.get_pending_transaction(hash).await?
+        {
+            *sender = Some((from, nonce));
+        }
+        let Some((from, nonce)) = *sender else {
+            return Ok(TxPoll::Waiting);
+        };
+
+        let mined_nonce: U64 = self
+            .request("eth_getTransactionCount", json!([from, "latest"]))
+            .await?;
+        if mined_nonce.to::<u64>() <= nonce {
+            return Ok(TxPoll::Waiting);
+        }
+
+        // The nonce was used - make sure it wasn't this transaction, mined
+        // between the receipt check and the nonce check
+        Ok(match self.get_transaction_receipt(hash).await? {
+            Some(receipt) => TxPoll::Mined(Box::new(receipt)),
+            None => TxPoll::Replaced,
+        })
+    }
+}
+
+/// Outcome of a single poll
+enum TxPoll {
+    Waiting,
+    Mined(Box<TransactionReceipt>),
+    Replaced,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockWallet;
+    use serde_json::Value;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const SENDER: &str = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045";
+
+    #[wasm_bindgen_test]
+    async fn detects_replacement_from_partial_transactions() {
+        // Pending transactions often come back without signature fields
+        let wallet = MockWallet::new(|method, _| match method {
+            "eth_getTransactionReceipt" => Ok(Value::Null),
+            "eth_getTransactionByHash" => Ok(json!({ "from": SENDER, "nonce": "0x3" })),
+            "eth_getTransactionCount" => Ok(json!("0x4")),
+            _ => Ok(Value::Null),
+        });
+        let provider = WindowProvider::from_transport(wallet.transport());
+        let hash = TxHash::repeat_byte(0xab);
+
+        let mut sender = None;
+        let poll = provider.poll_transaction(hash, &mut sender).await.unwrap();
+        assert!(matches!(poll, TxPoll::Replaced));
+        assert_eq!(sender, Some((SENDER.parse().unwrap(), 3)));
+    }
+
+    #[wasm_bindgen_test]
+    async fn waits_while_the_nonce_is_unused() {
+        let wallet = MockWallet::new(|method, _| match method {
+            "eth_getTransactionByHash" => Ok(json!({ "from": SENDER, "nonce": "0x3" })),
+            "eth_getTransactionCount" => Ok(json!("0x3")),
+            _ => Ok(Value::Null),
+        });
+        let provider = WindowProvider::from_transport(wallet.transport());
+
+        let mut sender = None;
+        let poll = provider
+            .poll_transaction(TxHash::repeat_byte(0xab), &mut sender)
+            .await
+            .unwrap();
+        assert!(matches!(poll, TxPoll::Waiting));
+    }
+}